@@ -0,0 +1,62 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use ascdump::AscParser;
+
+/// Forwards to the system allocator while counting allocations
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const LINES: usize = 10_000;
+
+fn sample_log() -> String {
+    let mut log =
+        String::from("date Mon Sep 14 10:11:12.345 am 2020\nbase hex  timestamps absolute\n");
+    for _ in 0..LINES / 2 {
+        log.push_str("// comment line\n");
+        log.push_str(
+            "   0.962604 3  368             Rx   d 4 cc 55 01 00  Length = 0 BitCount = 0 ID = 872\n",
+        );
+    }
+    log
+}
+
+fn allocations_while_iterating(c: &mut Criterion) {
+    let log = sample_log();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let frames = AscParser::new(log.as_bytes()).count();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "{} lines, {} frames: {} allocations ({:.2} per line)",
+        LINES,
+        frames,
+        allocations,
+        allocations as f64 / LINES as f64
+    );
+
+    c.bench_function("iterate over 10k lines with comments", |b| {
+        b.iter(|| AscParser::new(black_box(log.as_bytes())).count())
+    });
+}
+
+criterion_group!(benches, allocations_while_iterating);
+criterion_main!(benches);
@@ -0,0 +1,36 @@
+use std::hint::black_box;
+use std::str::FromStr;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use ascdump::{AscParser, CanFrame};
+
+const CLASSIC_LINE: &str =
+    "   0.962892 3  1f78c410x       Rx   d 8 02 00 00 00 24 00 70 03  Length = 0 BitCount = 0 ID = 528008208x";
+
+fn fd_line() -> String {
+    let payload = ["5a"; 64].join(" ");
+    format!(
+        "   7.392600 CANFD   1 Rx         6e    1 0 f 64 {}  0    0     3000        0        0        0        0        0",
+        payload
+    )
+}
+
+fn parse_lines(c: &mut Criterion) {
+    c.bench_function("parse classic line", |b| {
+        b.iter(|| CanFrame::from_str(black_box(CLASSIC_LINE)))
+    });
+
+    let fd_line = fd_line();
+    c.bench_function("parse 64 byte CANFD line", |b| {
+        b.iter(|| CanFrame::from_str(black_box(&fd_line)))
+    });
+
+    let log = format!("{}\n{}\n", CLASSIC_LINE, fd_line).repeat(5000);
+    c.bench_function("iterate over 10k lines", |b| {
+        b.iter(|| AscParser::new(black_box(log.as_bytes())).count())
+    });
+}
+
+criterion_group!(benches, parse_lines);
+criterion_main!(benches);
@@ -0,0 +1,478 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+
+use crate::CanFrame;
+
+/// Computes the bus load per time window as percentage of the nominal bitrate
+///
+/// Windows of `window` seconds are aligned to multiples of the window size and returned as
+/// `(window_start, load_percent)`, including windows without any frame. The bits of a frame are
+/// taken from its `BitCount` field if present, otherwise they are estimated from the frame
+/// layout without stuff bits. CAN FD data phases are counted at the nominal bitrate.
+pub fn bus_load<I>(frames: I, bitrate: f64, window: f64) -> Vec<(f64, f64)>
+where
+    I: IntoIterator,
+    I::Item: Borrow<CanFrame>,
+{
+    let mut bits_per_window: Vec<u64> = vec![];
+    let mut first_window = None;
+
+    for frame in frames {
+        let frame = frame.borrow();
+        let window_index = (frame.timestamp / window).floor() as i64;
+        let first = *first_window.get_or_insert(window_index);
+        // Frames are expected in time order, earlier ones are accounted to the first window
+        let index = (window_index - first).max(0) as usize;
+        if index >= bits_per_window.len() {
+            bits_per_window.resize(index + 1, 0);
+        }
+        bits_per_window[index] += frame_bits(frame);
+    }
+
+    let capacity = bitrate * window;
+    bits_per_window
+        .into_iter()
+        .enumerate()
+        .map(|(index, bits)| {
+            let start = (first_window.unwrap_or(0) + index as i64) as f64 * window;
+            (start, bits as f64 / capacity * 100.0)
+        })
+        .collect()
+}
+
+/// The load of a single bus, see [`bus_load_per_bus`]
+#[derive(Debug, PartialEq, Clone)]
+pub enum BusLoad {
+    /// The load per time window as returned by [`bus_load`]
+    Known(Vec<(f64, f64)>),
+    /// No bitrate was configured for the bus, only the number of frames is known
+    Unknown { frames: usize },
+}
+
+/// Computes the bus load per time window separately for every bus using its nominal bitrate
+///
+/// Buses are mapped to their bitrate in bit/s, buses missing in `bitrates` are reported as
+/// [`BusLoad::Unknown`]. Windows are aligned as for [`bus_load`].
+pub fn bus_load_per_bus<I>(
+    frames: I,
+    bitrates: &HashMap<u16, u32>,
+    window: f64,
+) -> HashMap<u16, BusLoad>
+where
+    I: IntoIterator,
+    I::Item: Borrow<CanFrame>,
+{
+    let mut per_bus: HashMap<u16, Vec<I::Item>> = HashMap::new();
+    for frame in frames {
+        per_bus
+            .entry(frame.borrow().bus_id)
+            .or_default()
+            .push(frame);
+    }
+
+    per_bus
+        .into_iter()
+        .map(|(bus, frames)| {
+            let load = match bitrates.get(&bus) {
+                Some(bitrate) => BusLoad::Known(bus_load(frames, *bitrate as f64, window)),
+                None => BusLoad::Unknown {
+                    frames: frames.len(),
+                },
+            };
+            (bus, load)
+        })
+        .collect()
+}
+
+/// Groups frames by their `(id, is_extended)` pair, keeping the order of frames within a group
+///
+/// Standard and extended frames sharing a numeric id end up in different groups. All frames are
+/// buffered, to process a single id while streaming filter the parser instead.
+pub fn group_by_id<I>(frames: I) -> HashMap<(u32, bool), Vec<CanFrame>>
+where
+    I: IntoIterator<Item = CanFrame>,
+{
+    let mut groups: HashMap<(u32, bool), Vec<CanFrame>> = HashMap::new();
+    for frame in frames {
+        groups
+            .entry((frame.id, frame.is_extended))
+            .or_default()
+            .push(frame);
+    }
+    groups
+}
+
+/// Computes the time deltas between consecutive frames of each `(id, is_extended)` pair
+///
+/// The deltas are keyed like the groups of [`group_by_id`] instead of by the bare id, as standard
+/// and extended frames sharing a numeric id are different messages. An id seen only once gets an
+/// empty list of deltas.
+pub fn cycle_times<I>(frames: I) -> HashMap<(u32, bool), Vec<f64>>
+where
+    I: IntoIterator,
+    I::Item: Borrow<CanFrame>,
+{
+    let mut last_seen: HashMap<(u32, bool), f64> = HashMap::new();
+    let mut deltas: HashMap<(u32, bool), Vec<f64>> = HashMap::new();
+    for frame in frames {
+        let frame = frame.borrow();
+        let key = (frame.id, frame.is_extended);
+        let cycle_times = deltas.entry(key).or_default();
+        if let Some(last) = last_seen.insert(key, frame.timestamp) {
+            cycle_times.push(frame.timestamp - last);
+        }
+    }
+    deltas
+}
+
+/// Returns the latest frame at or before `timestamp` for each `(id, is_extended)` pair
+///
+/// This reconstructs the state of the bus at a point in time. Ids without any frame up to then
+/// are left out. Frames do not need to be sorted, on equal timestamps the later frame wins.
+pub fn snapshot_at<I>(frames: I, timestamp: f64) -> HashMap<(u32, bool), CanFrame>
+where
+    I: IntoIterator,
+    I::Item: Borrow<CanFrame>,
+{
+    let mut snapshot: HashMap<(u32, bool), CanFrame> = HashMap::new();
+    for frame in frames {
+        let frame = frame.borrow();
+        if frame.timestamp > timestamp {
+            continue;
+        }
+        let key = (frame.id, frame.is_extended);
+        match snapshot.get(&key) {
+            Some(latest) if latest.timestamp > frame.timestamp => {}
+            _ => {
+                snapshot.insert(key, frame.clone());
+            }
+        }
+    }
+    snapshot
+}
+
+/// A period in which a cyclic frame was expected but not received
+#[derive(Debug, PartialEq, Clone)]
+pub struct Gap {
+    pub id: u32,
+    pub is_extended: bool,
+    /// The timestamp the missing frame was expected at, one cycle after the last one received
+    pub expected: f64,
+    /// The time in seconds the next frame arrived later than expected
+    pub missing: f64,
+}
+
+/// Finds gaps in cyclic frames given the expected cycle time per `(id, is_extended)` pair
+///
+/// A gap is reported whenever consecutive frames of an id are further apart than its cycle time
+/// plus `tolerance_ms`. The time before the first frame of an id is not considered a gap, as
+/// ECUs start sending at different times. Ids without an expected cycle time are ignored.
+pub fn find_gaps<I>(
+    frames: I,
+    expected_cycle_ms: &HashMap<(u32, bool), f64>,
+    tolerance_ms: f64,
+) -> Vec<Gap>
+where
+    I: IntoIterator,
+    I::Item: Borrow<CanFrame>,
+{
+    let mut last_seen: HashMap<(u32, bool), f64> = HashMap::new();
+    let mut gaps = vec![];
+    for frame in frames {
+        let frame = frame.borrow();
+        let key = (frame.id, frame.is_extended);
+        let cycle = match expected_cycle_ms.get(&key) {
+            Some(cycle_ms) => cycle_ms / 1000.0,
+            None => continue,
+        };
+
+        if let Some(last) = last_seen.insert(key, frame.timestamp) {
+            let delta = frame.timestamp - last;
+            if delta > cycle + tolerance_ms / 1000.0 {
+                gaps.push(Gap {
+                    id: frame.id,
+                    is_extended: frame.is_extended,
+                    expected: last + cycle,
+                    missing: delta - cycle,
+                });
+            }
+        }
+    }
+    gaps
+}
+
+/// The frames of an id that occur in only one of two logs, see [`diff`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct IdDiff {
+    pub id: u32,
+    pub is_extended: bool,
+    pub only_left: Vec<CanFrame>,
+    pub only_right: Vec<CanFrame>,
+}
+
+/// Compares two logs frame by frame, returning the frames without counterpart per id
+///
+/// Frames are matched by [`CanFrame::content_eq`], so timestamps and the order of frames within
+/// an id do not matter. Every frame matches at most one frame of the other log. Ids are
+/// returned in ascending order, ids without differences are left out.
+pub fn diff<I, J>(left: I, right: J) -> Vec<IdDiff>
+where
+    I: IntoIterator<Item = CanFrame>,
+    J: IntoIterator<Item = CanFrame>,
+{
+    let mut left = group_by_id(left);
+    let right = group_by_id(right);
+
+    let mut diffs = vec![];
+    for (key, right_frames) in right {
+        let left_frames = left.remove(&key).unwrap_or_default();
+
+        // Index the right frames by content, all fields compared besides the id
+        let mut unmatched: HashMap<(u16, usize, &[u8]), Vec<usize>> = HashMap::new();
+        for (index, frame) in right_frames.iter().enumerate().rev() {
+            unmatched
+                .entry((frame.bus_id, frame.length, &frame.payload))
+                .or_default()
+                .push(index);
+        }
+
+        let mut left_matched = vec![false; left_frames.len()];
+        let mut right_matched = vec![false; right_frames.len()];
+        for (index, frame) in left_frames.iter().enumerate() {
+            let content = (frame.bus_id, frame.length, frame.payload.as_slice());
+            if let Some(right_index) = unmatched.get_mut(&content).and_then(Vec::pop) {
+                left_matched[index] = true;
+                right_matched[right_index] = true;
+            }
+        }
+
+        let only_left = unmatched_frames(left_frames, left_matched);
+        let only_right = unmatched_frames(right_frames, right_matched);
+
+        if !only_left.is_empty() || !only_right.is_empty() {
+            diffs.push(IdDiff {
+                id: key.0,
+                is_extended: key.1,
+                only_left,
+                only_right,
+            });
+        }
+    }
+    diffs.extend(left.into_iter().map(|(key, frames)| IdDiff {
+        id: key.0,
+        is_extended: key.1,
+        only_left: frames,
+        only_right: vec![],
+    }));
+
+    diffs.sort_by_key(|diff| (diff.id, diff.is_extended));
+    diffs
+}
+
+fn unmatched_frames(frames: Vec<CanFrame>, matched: Vec<bool>) -> Vec<CanFrame> {
+    frames
+        .into_iter()
+        .zip(matched)
+        .filter(|(_, matched)| !matched)
+        .map(|(frame, _)| frame)
+        .collect()
+}
+
+/// The number of bits a frame occupies on the bus
+fn frame_bits(frame: &CanFrame) -> u64 {
+    match frame.bit_count {
+        Some(bits) if bits > 0 => bits as u64,
+        _ => {
+            // Start of frame up to the end of frame and interframe space
+            let overhead = match frame.is_extended {
+                true => 67,
+                false => 47,
+            };
+            let data = match frame.is_rtr {
+                true => 0,
+                false => 8 * frame.length as u64,
+            };
+            overhead + data
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::AscParser;
+
+    #[test]
+    fn compute_bus_load() {
+        // One frame of 125 bits per millisecond at 500 kbit/s uses a quarter of the bus
+        let frames: Vec<CanFrame> = (0..2000)
+            .map(|i| {
+                let mut frame = CanFrame::builder()
+                    .timestamp((i as f64 + 0.5) / 1000.0)
+                    .payload(&[0; 8])
+                    .build();
+                frame.bit_count = Some(125);
+                frame
+            })
+            .collect();
+
+        let load = bus_load(&frames, 500_000.0, 1.0);
+        assert_eq!(load.len(), 2);
+        assert_eq!(load[0], (0.0, 25.0));
+        assert_eq!(load[1], (1.0, 25.0));
+    }
+
+    #[test]
+    fn compute_bus_load_per_bus() {
+        // The same traffic of 125 bits per millisecond on buses of different bitrates
+        let mut frames: Vec<CanFrame> = (0..2000)
+            .map(|i| {
+                let mut frame = CanFrame::builder()
+                    .timestamp((i / 2) as f64 / 1000.0)
+                    .bus_id(i % 2 + 1)
+                    .build();
+                frame.bit_count = Some(125);
+                frame
+            })
+            .collect();
+        frames.push(CanFrame::builder().bus_id(3).build());
+        frames.push(CanFrame::builder().bus_id(3).build());
+
+        let mut bitrates = HashMap::new();
+        bitrates.insert(1, 500_000);
+        bitrates.insert(2, 250_000);
+        let loads = bus_load_per_bus(&frames, &bitrates, 1.0);
+
+        assert_eq!(loads.len(), 3);
+        assert_eq!(loads[&1], BusLoad::Known(vec![(0.0, 25.0)]));
+        assert_eq!(loads[&2], BusLoad::Known(vec![(0.0, 50.0)]));
+        assert_eq!(loads[&3], BusLoad::Unknown { frames: 2 });
+    }
+
+    #[test]
+    fn group_frames_by_id() {
+        let fixture = include_str!("../tests/fixtures/sample.asc");
+        let mut frames: Vec<CanFrame> = AscParser::new(fixture.as_bytes()).collect();
+        frames.push(CanFrame::builder().id(0x368).extended(true).build());
+
+        let groups = group_by_id(frames);
+        assert_eq!(groups.len(), 5);
+        assert_eq!(
+            groups[&(0x368, false)]
+                .iter()
+                .map(|frame| frame.timestamp)
+                .collect::<Vec<_>>(),
+            vec![0.962604, 1.5]
+        );
+        assert_eq!(groups[&(0x368, true)].len(), 1);
+        assert_eq!(groups[&(0x123, false)].len(), 2);
+        assert_eq!(groups[&(0x1F78C410, true)].len(), 1);
+        assert_eq!(groups[&(0x6E, false)].len(), 1);
+    }
+
+    #[test]
+    fn diff_logs_by_content() {
+        let frame = |timestamp: f64, id: u32, payload: &[u8]| {
+            CanFrame::builder()
+                .timestamp(timestamp)
+                .id(id)
+                .payload(payload)
+                .build()
+        };
+        let left = vec![
+            frame(0.1, 0x100, &[1]),
+            frame(0.2, 0x100, &[2]),
+            frame(0.3, 0x200, &[1]),
+            frame(0.4, 0x300, &[1]),
+            frame(0.5, 0x100, &[2]),
+        ];
+        let right = vec![
+            frame(1.1, 0x100, &[2]),
+            frame(1.2, 0x100, &[1]),
+            frame(1.3, 0x200, &[1]),
+            frame(1.4, 0x400, &[1]),
+        ];
+
+        let diffs = diff(left, right);
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(diffs[0].id, 0x100);
+        assert_eq!(diffs[0].only_left, vec![frame(0.5, 0x100, &[2])]);
+        assert!(diffs[0].only_right.is_empty());
+        assert_eq!(diffs[1].id, 0x300);
+        assert_eq!(diffs[1].only_left.len(), 1);
+        assert_eq!(diffs[2].id, 0x400);
+        assert_eq!(diffs[2].only_right, vec![frame(1.4, 0x400, &[1])]);
+    }
+
+    #[test]
+    fn take_snapshot() {
+        let fixture = include_str!("../tests/fixtures/sample.asc");
+        let frames: Vec<CanFrame> = AscParser::new(fixture.as_bytes()).collect();
+
+        let snapshot = snapshot_at(&frames, 1.5);
+        assert_eq!(snapshot.len(), 3);
+        assert_eq!(
+            snapshot[&(0x368, false)].payload,
+            vec![0xCC, 0x55, 0x02, 0x00]
+        );
+        assert_eq!(snapshot[&(0x123, false)].timestamp, 1.0);
+        assert_eq!(snapshot[&(0x1F78C410, true)].timestamp, 0.962892);
+        assert!(!snapshot.contains_key(&(0x6E, false)));
+
+        assert!(snapshot_at(&frames, 0.5).is_empty());
+    }
+
+    #[test]
+    fn compute_cycle_times() {
+        let frames: Vec<CanFrame> = [(0.5, 0x100), (0.75, 0x200), (1.5, 0x100), (3.5, 0x100)]
+            .iter()
+            .map(|(timestamp, id)| CanFrame::builder().timestamp(*timestamp).id(*id).build())
+            .collect();
+
+        let cycle_times = cycle_times(&frames);
+        assert_eq!(cycle_times.len(), 2);
+        assert_eq!(cycle_times[&(0x100, false)], vec![1.0, 2.0]);
+        assert!(cycle_times[&(0x200, false)].is_empty());
+    }
+
+    #[test]
+    fn find_dropped_frame() {
+        // A 10ms cycle starting late at 1s, with the frame at 1.05s missing
+        let mut frames: Vec<CanFrame> = (0..10)
+            .filter(|i| *i != 5)
+            .map(|i| {
+                CanFrame::builder()
+                    .timestamp(1.0 + i as f64 * 0.01)
+                    .id(0x100)
+                    .build()
+            })
+            .collect();
+        frames.push(CanFrame::builder().timestamp(1.5).id(0x200).build());
+
+        let mut expected = HashMap::new();
+        expected.insert((0x100, false), 10.0);
+        let gaps = find_gaps(&frames, &expected, 1.0);
+
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].id, 0x100);
+        assert!(!gaps[0].is_extended);
+        assert!((gaps[0].expected - 1.05).abs() < 1e-9);
+        assert!((gaps[0].missing - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn estimate_bits_without_bit_count() {
+        let frames = vec![
+            CanFrame::builder().timestamp(10.2).payload(&[0; 8]).build(),
+            CanFrame::builder()
+                .timestamp(12.7)
+                .extended(true)
+                .payload(&[0; 2])
+                .build(),
+        ];
+
+        let load = bus_load(frames, 1000.0, 1.0);
+        assert_eq!(load, vec![(10.0, 11.1), (11.0, 0.0), (12.0, 8.3)]);
+    }
+}
@@ -0,0 +1,175 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::AsyncBufRead;
+
+use crate::{AscHeader, AscParseError, AscParser, AscParserOptions, CanFrame};
+
+/// A parser reading frames from an asynchronous input, e.g. an upload to a web server
+///
+/// Lines are parsed like by [`AscParser::try_frames`], so the stream yields an error for every
+/// line that is neither a frame nor skipped like header lines are. The stream ends at the end of
+/// input or at the first error reading it.
+pub struct AsyncAscParser<R> {
+    reader: R,
+    line: Vec<u8>,
+    /// A parser without input that parses the lines read, keeping track of the header
+    state: AscParser<io::Empty>,
+}
+
+impl<R> AsyncAscParser<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, AscParserOptions::default())
+    }
+
+    /// Creates a parser with the given options, see [`AscParserOptions`]
+    pub fn with_options(reader: R, options: AscParserOptions) -> Self {
+        Self {
+            reader,
+            line: vec![],
+            state: AscParser::with_options(io::empty(), options),
+        }
+    }
+
+    /// The header information encountered so far
+    pub fn header(&self) -> &AscHeader {
+        self.state.header()
+    }
+
+    /// Reads the next line into the line buffer, ready with false at the end of input
+    fn poll_read_line(&mut self, cx: &mut Context<'_>) -> Poll<bool> {
+        loop {
+            let available = match ready!(Pin::new(&mut self.reader).poll_fill_buf(cx)) {
+                Ok(available) => available,
+                Err(_) => return Poll::Ready(false),
+            };
+            if available.is_empty() {
+                // The last line may lack a terminator
+                return Poll::Ready(!self.line.is_empty());
+            }
+            let (consumed, complete) = match available.iter().position(|byte| *byte == b'\n') {
+                Some(end) => (end + 1, true),
+                None => (available.len(), false),
+            };
+            self.line.extend_from_slice(&available[..consumed]);
+            Pin::new(&mut self.reader).consume(consumed);
+            if complete {
+                return Poll::Ready(true);
+            }
+        }
+    }
+}
+
+impl<R> Stream for AsyncAscParser<R>
+where
+    R: AsyncBufRead + Unpin,
+{
+    type Item = Result<CanFrame, AscParseError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        while !this.state.stopped {
+            if !ready!(this.poll_read_line(cx)) {
+                break;
+            }
+            let line = std::mem::take(&mut this.line);
+            let item = match String::from_utf8(line) {
+                Ok(line) => {
+                    this.state.buffer = line;
+                    let item = match this.state.accept_buffer() {
+                        true => match this.state.parse_buffer() {
+                            Ok(mut frame) => {
+                                frame.timestamp = this.state.since_start(frame.timestamp);
+                                Some(Ok(frame))
+                            }
+                            // Event and error frame lines are no frames, but no errors either
+                            Err(_) if this.state.skip_event() => None,
+                            Err(err) => Some(Err(err)),
+                        },
+                        false => None,
+                    };
+                    this.line = std::mem::take(&mut this.state.buffer).into_bytes();
+                    item
+                }
+                Err(err) => {
+                    let item = Some(Err(AscParseError::InvalidEncoding {
+                        str: err.utf8_error().to_string(),
+                    }));
+                    this.line = err.into_bytes();
+                    item
+                }
+            };
+            // The line buffer is reused for every line
+            this.line.clear();
+            if item.is_some() {
+                return Poll::Ready(item);
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::future::poll_fn;
+
+    #[test]
+    fn stream_frames_from_async_reader() {
+        let input: &[u8] = b"base hex  timestamps absolute\n\
+            0.962604 3 368 Rx d 4 cc 55 01 00\r\n\
+            0.000000 Start of measurement\n\
+            0.962604 3 368 Rx d 4 cc 55 __ 00\n\
+            7.392600 CANFD 1 Rx 6e 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0";
+        let reader = tokio::io::BufReader::with_capacity(8, input);
+        let mut parser = AsyncAscParser::new(reader);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("Failed to build runtime");
+        let items = runtime.block_on(async {
+            let mut items = vec![];
+            while let Some(item) = poll_fn(|cx| Pin::new(&mut parser).poll_next(cx)).await {
+                items.push(item);
+            }
+            items
+        });
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].as_ref().map(|frame| frame.id), Ok(0x368));
+        assert!(matches!(
+            items[1],
+            Err(AscParseError::InvalidPayload { .. })
+        ));
+        assert_eq!(items[2].as_ref().map(|frame| frame.length), Ok(6));
+        assert_eq!(parser.header().base, crate::NumericBase::Hex);
+    }
+
+    #[test]
+    fn count_deltas_of_error_frames() {
+        let input: &[u8] = b"base hex  timestamps relative\n\
+            0.5 1 100 Rx d 1 01\n\
+            0.25 1 ErrorFrame\n\
+            0.25 1 100 Rx d 1 02\n";
+        let mut parser = AsyncAscParser::new(input);
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("Failed to build runtime");
+        let timestamps = runtime.block_on(async {
+            let mut timestamps = vec![];
+            while let Some(item) = poll_fn(|cx| Pin::new(&mut parser).poll_next(cx)).await {
+                timestamps.push(item.map(|frame| frame.timestamp));
+            }
+            timestamps
+        });
+
+        assert_eq!(timestamps, vec![Ok(0.5), Ok(1.0)]);
+    }
+}
@@ -1,27 +1,1375 @@
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
 use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::iter;
+use std::num::ParseIntError;
+use std::process;
+use std::rc::Rc;
 
 extern crate clap;
-use clap::{App, Arg};
+use clap::error::ErrorKind;
+use clap::{value_parser, Arg, ArgAction, Command};
 
-use ascdump::AscParser;
+use ascdump::{
+    bus_load_per_bus, count_frames, remap_bus, AscHeader, AscParser, BusLoad, CanFrame, Follow,
+    LocatedError, PayloadMatch, PcapngWriter,
+};
 
-fn main() {
-    let args = App::new("ascdump")
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct JsonFrame<'a> {
+    timestamp: JsonTimestamp,
+    bus: u16,
+    id: u32,
+    length: usize,
+    payload: &'a [u8],
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+#[serde(untagged)]
+enum JsonTimestamp {
+    Seconds(f64),
+    WallClock(String),
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Debug,
+    Json,
+    Csv,
+    Pcap,
+    CandumpLog,
+}
+
+/// How payload bytes are rendered in the debug and csv output
+#[derive(Clone, Copy)]
+enum PayloadFormat {
+    Hex,
+    Dec,
+    Bin,
+}
+
+impl PayloadFormat {
+    fn render(self, payload: &[u8], separator: &str) -> String {
+        let bytes: Vec<String> = payload
+            .iter()
+            .map(|byte| match self {
+                PayloadFormat::Hex => format!("{:02X}", byte),
+                PayloadFormat::Dec => format!("{}", byte),
+                PayloadFormat::Bin => format!("{:08b}", byte),
+            })
+            .collect();
+        bytes.join(separator)
+    }
+}
+
+/// Debug rendering of a frame with the payload in the requested format, optionally with the
+/// timestamp as wall-clock time
+struct DebugFrame<'a> {
+    frame: &'a CanFrame,
+    payload_format: PayloadFormat,
+    wall_clock: Option<String>,
+}
+
+impl fmt::Debug for DebugFrame<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let DebugFrame {
+            frame,
+            payload_format,
+            wall_clock,
+        } = self;
+        // Starting from the derived rendering keeps every field of the frame, only timestamp
+        // and payload are swapped
+        let mut debug = format!("{:?}", frame);
+        if let Some(wall_clock) = wall_clock {
+            debug = debug.replacen(
+                &format!("{{ timestamp: {:?},", frame.timestamp),
+                &format!("{{ timestamp: {},", wall_clock),
+                1,
+            );
+        }
+        debug = debug.replacen(
+            &format!("payload: {:?},", frame.payload),
+            &format!(
+                "payload: [{}],",
+                payload_format.render(&frame.payload, ", ")
+            ),
+            1,
+        );
+        f.write_str(&debug)
+    }
+}
+
+const CSV_HEADER: &str = "timestamp,bus,id,length,payload";
+
+#[cfg(feature = "dbc")]
+use ascdump::Dbc;
+
+/// Stand-in for builds without the dbc feature, which reject --dbc
+#[cfg(not(feature = "dbc"))]
+struct Dbc;
+
+#[cfg(feature = "dbc")]
+fn read_dbc(path: &str) -> Result<Dbc, String> {
+    ascdump::read_dbc(path).map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "dbc"))]
+fn read_dbc(_path: &str) -> Result<Dbc, String> {
+    unreachable!("--dbc requires the dbc feature")
+}
+
+/// Prints the decoded signals of a frame, e.g. `0.962604 can3 368 Mode=204 Speed=160.5`
+///
+/// Frames without any signal in the database are left out.
+#[cfg(feature = "dbc")]
+fn print_signals(out: &mut dyn Write, frame: &CanFrame, dbc: &Dbc) -> io::Result<()> {
+    let signals = frame.decode(dbc);
+    if signals.is_empty() {
+        return Ok(());
+    }
+    let signals: Vec<String> = signals
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    let id = match frame.is_extended {
+        true => format!("{:08X}", frame.id),
+        false => format!("{:03X}", frame.id),
+    };
+    writeln!(
+        out,
+        "{:.6} can{} {} {}",
+        frame.timestamp,
+        frame.bus_id,
+        id,
+        signals.join(" ")
+    )
+}
+
+#[cfg(not(feature = "dbc"))]
+fn print_signals(_out: &mut dyn Write, _frame: &CanFrame, _dbc: &Dbc) -> io::Result<()> {
+    Ok(())
+}
+
+/// Prints the decoded signals as csv with a column per signal of the database, e.g. for pandas
+///
+/// Every frame with any signal in the database adds a row. Signals keep their last value in the
+/// rows of other frames, except for multiplexed signals, which are left blank unless the frame
+/// carries them. Columns are named after message and signal, e.g. `Status.Speed`.
+#[cfg(feature = "dbc")]
+fn print_signal_table(frames: impl Iterator<Item = CanFrame>, dbc: &Dbc) {
+    use can_dbc::{MessageId, MultiplexIndicator};
+
+    let mut header = vec![String::from("timestamp")];
+    let mut multiplexed = vec![];
+    // The first column of each message by id
+    let mut messages = HashMap::new();
+    for message in &dbc.messages {
+        let key = match message.id {
+            MessageId::Standard(id) => (id as u32, false),
+            MessageId::Extended(id) => (id, true),
+        };
+        messages.insert(key, (multiplexed.len(), message));
+        for signal in &message.signals {
+            header.push(format!("{}.{}", message.name, signal.name));
+            multiplexed.push(matches!(
+                signal.multiplexer_indicator,
+                MultiplexIndicator::MultiplexedSignal(_)
+                    | MultiplexIndicator::MultiplexorAndMultiplexedSignal(_)
+            ));
+        }
+    }
+    println!("{}", header.join(","));
+
+    let mut values: Vec<Option<f64>> = vec![None; multiplexed.len()];
+    for frame in frames {
+        let (first, message) = match messages.get(&(frame.id, frame.is_extended)) {
+            Some(message) => *message,
+            None => continue,
+        };
+        let signals = frame.decode(dbc);
+        if signals.is_empty() {
+            continue;
+        }
+
+        for (value, multiplexed) in values.iter_mut().zip(&multiplexed) {
+            if *multiplexed {
+                *value = None;
+            }
+        }
+        for (name, value) in signals {
+            if let Some(index) = message.signals.iter().position(|s| s.name == name) {
+                values[first + index] = Some(value);
+            }
+        }
+
+        let row: Vec<String> = values
+            .iter()
+            .map(|value| value.map(|value| value.to_string()).unwrap_or_default())
+            .collect();
+        println!("{:.6},{}", frame.timestamp, row.join(","));
+    }
+}
+
+#[cfg(not(feature = "dbc"))]
+fn print_signal_table(_frames: impl Iterator<Item = CanFrame>, _dbc: &Dbc) {
+    unreachable!("signals requires the dbc feature")
+}
+
+/// Prints a frame, the payload format defaults to the `Debug` rendering and hex for csv
+///
+/// With `wall_clock` set, the timestamp is taken as seconds since the Unix epoch and printed as
+/// ISO 8601 date and time, except for candump logs which record these seconds as they are.
+fn print_frame(
+    out: &mut dyn Write,
+    frame: &CanFrame,
+    format: Format,
+    payload_format: Option<PayloadFormat>,
+    wall_clock: bool,
+) -> io::Result<()> {
+    let wall_clock = match (wall_clock, format) {
+        (_, Format::CandumpLog) | (false, _) => None,
+        (true, _) => Some(wall_clock_time(frame.timestamp)),
+    };
+    match format {
+        Format::Debug => match (payload_format, wall_clock) {
+            (None, None) => writeln!(out, "{:?}", frame),
+            (payload_format, wall_clock) => {
+                let debug_frame = DebugFrame {
+                    frame,
+                    // The decimal rendering matches the `Debug` rendering of the payload
+                    payload_format: payload_format.unwrap_or(PayloadFormat::Dec),
+                    wall_clock,
+                };
+                writeln!(out, "{:?}", debug_frame)
+            }
+        },
+        #[cfg(feature = "serde")]
+        Format::Json => {
+            let json_frame = JsonFrame {
+                timestamp: match wall_clock {
+                    Some(wall_clock) => JsonTimestamp::WallClock(wall_clock),
+                    None => JsonTimestamp::Seconds(frame.timestamp),
+                },
+                bus: frame.bus_id,
+                id: frame.id,
+                length: frame.length,
+                payload: &frame.payload,
+            };
+            match serde_json::to_string(&json_frame) {
+                Ok(json) => writeln!(out, "{}", json),
+                Err(err) => {
+                    eprintln!("cannot serialize frame: {}", err);
+                    Ok(())
+                }
+            }
+        }
+        #[cfg(not(feature = "serde"))]
+        Format::Json => unreachable!("json output requires the serde feature"),
+        Format::Csv => {
+            let payload = payload_format
+                .unwrap_or(PayloadFormat::Hex)
+                .render(&frame.payload, " ");
+            let timestamp = wall_clock.unwrap_or_else(|| format!("{:.6}", frame.timestamp));
+            writeln!(
+                out,
+                "{},{},{:08X},{},{}",
+                timestamp, frame.bus_id, frame.id, frame.length, payload
+            )
+        }
+        Format::CandumpLog => writeln!(out, "{}", candump_line(frame)),
+        Format::Pcap => unreachable!("pcap output is written by a PcapngWriter"),
+    }
+}
+
+/// Renders a frame like `candump -l`, e.g. `(0.962604) can3 368#CC550100`
+///
+/// CAN FD frames are separated by `##` followed by a digit carrying the BRS and ESI flags,
+/// remote frames end in `#R`.
+fn candump_line(frame: &CanFrame) -> String {
+    let id = match frame.is_extended {
+        true => format!("{:08X}", frame.id),
+        false => format!("{:03X}", frame.id),
+    };
+    let data = match (frame.is_fd, frame.is_rtr) {
+        (true, _) => {
+            let flags = frame.brs as u8 | (frame.esi as u8) << 1;
+            format!(
+                "#{:X}{}",
+                flags,
+                PayloadFormat::Hex.render(&frame.payload, "")
+            )
+        }
+        (false, true) => String::from("R"),
+        (false, false) => PayloadFormat::Hex.render(&frame.payload, ""),
+    };
+    format!(
+        "({:.6}) can{} {}#{}",
+        frame.timestamp, frame.bus_id, id, data
+    )
+}
+
+/// Writes the frames as pcapng file
+fn write_pcap(out: &mut dyn Write, frames: impl Iterator<Item = CanFrame>) -> io::Result<()> {
+    PcapngWriter::new(io::BufWriter::new(out)).write_frames(frames)
+}
+
+/// Seconds from the Unix epoch to the measurement start of the header, if declared
+///
+/// ASC files do not record a time zone, so the start is taken as UTC.
+#[cfg(feature = "chrono")]
+fn start_offset(header: &AscHeader) -> Option<f64> {
+    header
+        .start_time()
+        .map(|start| start.and_utc().timestamp_micros() as f64 / 1e6)
+}
+
+#[cfg(not(feature = "chrono"))]
+fn start_offset(_header: &AscHeader) -> Option<f64> {
+    None
+}
+
+/// Renders seconds since the Unix epoch as ISO 8601 date and time without time zone, e.g.
+/// `2020-09-14T10:11:13.307604`
+#[cfg(feature = "chrono")]
+fn wall_clock_time(seconds: f64) -> String {
+    match chrono::DateTime::from_timestamp_micros((seconds * 1e6).round() as i64) {
+        Some(time) => time.naive_utc().format("%Y-%m-%dT%H:%M:%S%.6f").to_string(),
+        None => format!("{:.6}", seconds),
+    }
+}
+
+#[cfg(not(feature = "chrono"))]
+fn wall_clock_time(_seconds: f64) -> String {
+    unreachable!("--absolute-time requires the chrono feature")
+}
+
+/// Parses a CAN id given as `368`, `0x368` or with the extended `x` suffix like `1f78c410x`
+fn parse_id(s: &str) -> Result<u32, String> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    let digits = digits.strip_suffix('x').unwrap_or(digits);
+    u32::from_str_radix(digits, 16).map_err(|err| format!("invalid CAN id {:?}: {}", s, err))
+}
+
+/// Reads a file listing one CAN id per line, see [`parse_id`]
+///
+/// Empty lines and comments starting with `#` or `//` are skipped, also after an id.
+fn read_id_file(path: &str) -> Result<HashSet<u32>, String> {
+    let content = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+    let mut ids = HashSet::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let line = line.split("//").next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let id = parse_id(line).map_err(|err| format!("{}:{}: {}", path, number + 1, err))?;
+        ids.insert(id);
+    }
+    Ok(ids)
+}
+
+/// Renders an id like in ASC files, extended ids carry an `x` suffix
+fn format_id(id: u32, is_extended: bool) -> String {
+    match is_extended {
+        true => format!("{:X}x", id),
+        false => format!("{:X}", id),
+    }
+}
+
+/// Prints the frames found in only one of the logs, per id and optionally frame by frame
+///
+/// The output follows the unified diff convention, frames only in the first log are marked
+/// with `-` and those only in the second log with `+`.
+fn print_diff(left: &str, right: &str, verbose: bool) {
+    let read = |path: &str| -> Vec<CanFrame> {
+        match open_input(path, false, false) {
+            Ok(input) => AscParser::new(decompress(input, path.ends_with(".gz"))).collect(),
+            Err(err) => {
+                eprintln!("error: cannot open {}: {}", path, err);
+                process::exit(EXIT_OPEN_FAILURE);
+            }
+        }
+    };
+    let diffs = ascdump::diff(read(left), read(right));
+
+    println!("--- {}", left);
+    println!("+++ {}", right);
+    for diff in &diffs {
+        println!(
+            "{} -{} +{}",
+            format_id(diff.id, diff.is_extended),
+            diff.only_left.len(),
+            diff.only_right.len()
+        );
+        if verbose {
+            for frame in &diff.only_left {
+                println!("-{}", frame.to_asc_line());
+            }
+            for frame in &diff.only_right {
+                println!("+{}", frame.to_asc_line());
+            }
+        }
+    }
+}
+
+/// Prints the number of frames per id and their share of all frames, busiest ids first
+fn print_histogram(out: &mut dyn Write, frames: impl Iterator<Item = CanFrame>) -> io::Result<()> {
+    let mut counts: Vec<((u32, bool), usize)> = ascdump::group_by_id(frames)
+        .into_iter()
+        .map(|(key, frames)| (key, frames.len()))
+        .collect();
+    counts
+        .sort_by(|(a_key, a_count), (b_key, b_count)| b_count.cmp(a_count).then(a_key.cmp(b_key)));
+
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+    for ((id, is_extended), count) in counts {
+        writeln!(
+            out,
+            "{} {} {:.2}%",
+            format_id(id, is_extended),
+            count,
+            count as f64 / total as f64 * 100.0
+        )?;
+    }
+    Ok(())
+}
+
+/// Parses a bitrate given as `<bus>:<bit/s>`, e.g. `1:500000`
+fn parse_bitrate(s: &str) -> Result<(u16, u32), String> {
+    let invalid = |err: String| format!("invalid bitrate {:?}: {}", s, err);
+    let (bus, rate) = s
+        .split_once(':')
+        .ok_or_else(|| invalid(String::from("expected <bus>:<bitrate>")))?;
+    let bus = bus
+        .parse()
+        .map_err(|err: ParseIntError| invalid(err.to_string()))?;
+    let rate = rate
+        .parse()
+        .map_err(|err: ParseIntError| invalid(err.to_string()))?;
+    Ok((bus, rate))
+}
+
+/// Parses a bus mapping given as `<from>:<to>`, e.g. `1:5`
+fn parse_remap(s: &str) -> Result<(u16, u16), String> {
+    let invalid = |err: String| format!("invalid bus mapping {:?}: {}", s, err);
+    let (from, to) = s
+        .split_once(':')
+        .ok_or_else(|| invalid(String::from("expected <from>:<to>")))?;
+    let from = from
+        .parse()
+        .map_err(|err: ParseIntError| invalid(err.to_string()))?;
+    let to = to
+        .parse()
+        .map_err(|err: ParseIntError| invalid(err.to_string()))?;
+    Ok((from, to))
+}
+
+/// Prints the average and peak load per bus over windows of one second
+fn print_bus_load(
+    out: &mut dyn Write,
+    frames: &[CanFrame],
+    bitrates: &HashMap<u16, u32>,
+) -> io::Result<()> {
+    let loads: BTreeMap<u16, BusLoad> = bus_load_per_bus(frames, bitrates, 1.0)
+        .into_iter()
+        .collect();
+    for (bus, load) in loads {
+        match load {
+            BusLoad::Known(windows) => {
+                let average =
+                    windows.iter().map(|(_, load)| load).sum::<f64>() / windows.len() as f64;
+                let peak = windows.iter().map(|(_, load)| *load).fold(0.0, f64::max);
+                writeln!(
+                    out,
+                    "bus {} load: {:.2}% average, {:.2}% peak",
+                    bus, average, peak
+                )?;
+            }
+            BusLoad::Unknown { .. } => writeln!(out, "bus {} load: unknown", bus)?,
+        }
+    }
+    Ok(())
+}
+
+/// Aggregate statistics collected in a single pass over the frames
+#[derive(Default)]
+struct Stats {
+    frames: usize,
+    per_bus: BTreeMap<u16, usize>,
+    ids: HashSet<(u32, bool)>,
+    min_timestamp: Option<f64>,
+    max_timestamp: Option<f64>,
+}
+
+impl Stats {
+    fn add(&mut self, frame: &CanFrame) {
+        self.frames += 1;
+        *self.per_bus.entry(frame.bus_id).or_insert(0) += 1;
+        self.ids.insert((frame.id, frame.is_extended));
+        self.min_timestamp = Some(
+            self.min_timestamp
+                .map_or(frame.timestamp, |min| min.min(frame.timestamp)),
+        );
+        self.max_timestamp = Some(
+            self.max_timestamp
+                .map_or(frame.timestamp, |max| max.max(frame.timestamp)),
+        );
+    }
+
+    fn print(&self, out: &mut dyn Write) -> io::Result<()> {
+        writeln!(out, "frames: {}", self.frames)?;
+        for (bus, count) in &self.per_bus {
+            writeln!(out, "bus {}: {}", bus, count)?;
+        }
+        writeln!(out, "unique ids: {}", self.ids.len())?;
+        if let (Some(min), Some(max)) = (self.min_timestamp, self.max_timestamp) {
+            let duration = max - min;
+            writeln!(out, "first timestamp: {:.6}", min)?;
+            writeln!(out, "last timestamp: {:.6}", max)?;
+            writeln!(out, "duration: {:.6}", duration)?;
+            if duration > 0.0 {
+                writeln!(
+                    out,
+                    "frames per second: {:.3}",
+                    self.frames as f64 / duration
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "flate2")]
+fn decompress(input: Box<dyn Read>, gzip: bool) -> Box<dyn Read> {
+    match gzip {
+        true => Box::new(flate2::read::MultiGzDecoder::new(input)),
+        false => input,
+    }
+}
+
+#[cfg(not(feature = "flate2"))]
+fn decompress(input: Box<dyn Read>, _gzip: bool) -> Box<dyn Read> {
+    input
+}
+
+/// Maps the file into memory, falling back to buffered reads if mapping fails
+#[cfg(feature = "memmap2")]
+fn map_file(file: File) -> Box<dyn Read> {
+    // Safety: the mapping is only read from, changes to the file while it is being parsed at
+    // worst garble the parsed frames
+    match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(mmap) => Box::new(io::Cursor::new(mmap)),
+        Err(_) => Box::new(file),
+    }
+}
+
+#[cfg(not(feature = "memmap2"))]
+fn map_file(file: File) -> Box<dyn Read> {
+    Box::new(file)
+}
+
+/// Compression of the output file, see `--compress`
+#[derive(Clone, Copy)]
+enum Compression {
+    Gzip,
+    Zstd,
+}
+
+/// Where the output is written to, optionally compressed
+enum Output {
+    Plain(Box<dyn Write>),
+    #[cfg(feature = "flate2")]
+    Gzip(flate2::write::GzEncoder<Box<dyn Write>>),
+    #[cfg(feature = "zstd")]
+    Zstd(zstd::stream::write::Encoder<'static, Box<dyn Write>>),
+}
+
+impl Output {
+    /// Creates the output file, `-` or no path denoting stdout
+    ///
+    /// Stdout is left unbuffered beyond its line buffering, so `--follow` prints frames as they
+    /// arrive.
+    fn open(path: Option<&str>, compression: Option<Compression>) -> io::Result<Self> {
+        let writer: Box<dyn Write> = match path {
+            Some(path) if path != "-" => Box::new(io::BufWriter::new(File::create(path)?)),
+            _ => Box::new(io::stdout()),
+        };
+        Ok(match compression {
+            None => Output::Plain(writer),
+            #[cfg(feature = "flate2")]
+            Some(Compression::Gzip) => Output::Gzip(flate2::write::GzEncoder::new(
+                writer,
+                flate2::Compression::default(),
+            )),
+            #[cfg(feature = "zstd")]
+            Some(Compression::Zstd) => Output::Zstd(zstd::stream::write::Encoder::new(writer, 0)?),
+            #[allow(unreachable_patterns)]
+            Some(_) => unreachable!("compression is rejected without its feature"),
+        })
+    }
+
+    /// Ends the compressed stream and flushes everything written
+    fn finish(self) -> io::Result<()> {
+        match self {
+            Output::Plain(mut writer) => writer.flush(),
+            #[cfg(feature = "flate2")]
+            Output::Gzip(encoder) => encoder.finish()?.flush(),
+            #[cfg(feature = "zstd")]
+            Output::Zstd(encoder) => encoder.finish()?.flush(),
+        }
+    }
+
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Output::Plain(writer) => writer,
+            #[cfg(feature = "flate2")]
+            Output::Gzip(encoder) => encoder,
+            #[cfg(feature = "zstd")]
+            Output::Zstd(encoder) => encoder,
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer().flush()
+    }
+}
+
+/// Exit code for inputs and outputs that cannot be opened or written
+const EXIT_OPEN_FAILURE: i32 = 1;
+
+/// Exit code for lines failing to parse with `--strict` or `--quiet`
+const EXIT_PARSE_FAILURE: i32 = 2;
+
+/// Exit code for frames failing to be sent by `replay`
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+const EXIT_SEND_FAILURE: i32 = 3;
+
+/// Opens an input file, `-` denoting stdin
+fn open_input(path: &str, follow: bool, mmap: bool) -> io::Result<Box<dyn Read>> {
+    if path == "-" {
+        return Ok(Box::new(io::stdin()));
+    }
+    let file = File::open(path)?;
+    Ok(match (follow, mmap) {
+        (true, _) => Box::new(Follow::new(file)),
+        (false, true) => map_file(file),
+        (false, false) => Box::new(file),
+    })
+}
+
+fn parse_seconds(s: &str) -> Result<f64, String> {
+    s.parse::<f64>()
+        .map_err(|err| format!("invalid timestamp {:?}: {}", s, err))
+}
+
+fn command() -> Command {
+    let command = Command::new("ascdump")
         .version("0.1")
         .author("Christoph Weinsheimer <christoph.weinsheimer@esrlabs.com>")
         .about("Does awesome things")
         .arg(
-            Arg::with_name("INPUT")
-                .help("Sets the input asc file to use")
-                .required(true)
+            Arg::new("INPUT")
+                .help(
+                    "Sets the input asc files to use, reads from stdin if omitted or '-'. \
+                     Multiple files are processed in sequence",
+                )
+                .action(ArgAction::Append)
+                .num_args(1..)
                 .index(1),
         )
-        .get_matches();
+        .arg(
+            Arg::new("id")
+                .long("id")
+                .value_name("hex")
+                .help("Only print frames with the given CAN id, may be repeated")
+                .action(ArgAction::Append)
+                .value_parser(parse_id),
+        )
+        .arg(Arg::new("id-file").long("id-file").value_name("file").help(
+            "Only print frames with the CAN ids listed in the file, one per line. \
+                     Comments start with #, ids add to those given with --id",
+        ))
+        .arg(
+            Arg::new("exclude-id-file")
+                .long("exclude-id-file")
+                .value_name("file")
+                .help("Omits frames with the CAN ids listed in the file, like --id-file"),
+        )
+        .arg(
+            Arg::new("bus")
+                .long("bus")
+                .value_name("n")
+                .help(
+                    "Only print frames on the given bus, may be repeated. \
+                     Combined with --id only frames matching both are printed",
+                )
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(u16)),
+        )
+        .arg(
+            Arg::new("remap")
+                .long("remap")
+                .value_name("from:to")
+                .help(
+                    "Rewrites the bus id of frames on the first bus to the second, may be \
+                     repeated. --bus selects buses after remapping",
+                )
+                .action(ArgAction::Append)
+                .value_parser(parse_remap),
+        )
+        .arg(
+            Arg::new("match")
+                .long("match")
+                .value_name("offset:byte[/mask]")
+                .help(
+                    "Only print frames whose payload byte at the offset equals the hex value, \
+                     comparing only the bits of the optional mask. May be repeated, frames \
+                     need to match all",
+                )
+                .action(ArgAction::Append)
+                .value_parser(|s: &str| s.parse::<PayloadMatch>().map_err(|err| err.to_string())),
+        )
+        .arg(
+            Arg::new("from")
+                .long("from")
+                .value_name("seconds")
+                .help("Only print frames at or after the given timestamp")
+                .value_parser(parse_seconds),
+        )
+        .arg(
+            Arg::new("to")
+                .long("to")
+                .value_name("seconds")
+                .help("Only print frames at or before the given timestamp")
+                .value_parser(parse_seconds),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_name("format")
+                .help(
+                    "Sets the output format, json prints one object per line, pcap writes \
+                     a pcapng file for Wireshark and candumplog lines for canplayer",
+                )
+                .value_parser(["debug", "json", "csv", "pcap", "candumplog"])
+                .default_value("debug"),
+        )
+        .arg(
+            Arg::new("payload-format")
+                .long("payload-format")
+                .value_name("base")
+                .help("Sets how payload bytes are printed in the debug and csv output")
+                .value_parser(["hex", "dec", "bin"]),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("file")
+                .help("Writes the output to the file instead of stdout"),
+        )
+        .arg(
+            Arg::new("compress")
+                .long("compress")
+                .value_name("algorithm")
+                .help("Compresses the output, implied for output files ending in .gz or .zst")
+                .value_parser(["gzip", "zstd"]),
+        )
+        .arg(
+            Arg::new("absolute-time")
+                .long("absolute-time")
+                .help(
+                    "Prints timestamps as wall-clock time from the date in the header, \
+                     seconds since the Unix epoch for candumplog. Logs without a date keep \
+                     their relative timestamps",
+                )
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["dbc", "stats", "histogram", "count"]),
+        )
+        .arg(
+            Arg::new("dbc").long("dbc").value_name("file").help(
+                "Prints the signals decoded with the given DBC database instead of the frames",
+            ),
+        )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .help("Prints aggregate statistics instead of the frames")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("count")
+                .long("count")
+                .help("Prints the number of frames only, without decoding their payloads")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all([
+                    "id",
+                    "id-file",
+                    "exclude-id-file",
+                    "bus",
+                    "match",
+                    "from",
+                    "to",
+                    "dbc",
+                    "stats",
+                    "follow",
+                    "strict",
+                    "quiet",
+                ]),
+        )
+        .arg(
+            Arg::new("bitrate")
+                .long("bitrate")
+                .value_name("bus:bitrate")
+                .help(
+                    "Sets the nominal bitrate of a bus in bit/s to print its load with --stats, \
+                     may be repeated",
+                )
+                .action(ArgAction::Append)
+                .value_parser(parse_bitrate)
+                .requires("stats"),
+        )
+        .arg(
+            Arg::new("histogram")
+                .long("histogram")
+                .help("Prints the number of frames per id instead of the frames, busiest first")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["stats", "dbc"]),
+        )
+        .arg(
+            Arg::new("follow")
+                .long("follow")
+                .short('f')
+                .help("Keeps waiting for frames appended to the input file, like tail -f")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("gzip")
+                .long("gzip")
+                .help("Decompresses the input with gzip, implied for files ending in .gz")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("mmap")
+                .long("mmap")
+                .help("Maps input files into memory instead of reading them")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("follow"),
+        )
+        .arg(
+            Arg::new("strict")
+                .long("strict")
+                .help("Aborts at the first line failing to parse")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("quiet")
+                .long("quiet")
+                .short('q')
+                .help("Suppresses the warnings about lines failing to parse, which still exit with code 2")
+                .action(ArgAction::SetTrue),
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("diff")
+                .about("Lists the frames found in only one of two logs, matched by content")
+                .arg(Arg::new("LEFT").required(true).index(1))
+                .arg(Arg::new("RIGHT").required(true).index(2))
+                .arg(
+                    Arg::new("verbose")
+                        .long("verbose")
+                        .short('v')
+                        .help("Prints each differing frame in addition to the counts per id")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("signals")
+                .about(
+                    "Prints the signals decoded with a DBC database as csv, one column per \
+                     signal and one row per frame",
+                )
+                .arg(Arg::new("FILE").required(true).index(1))
+                .arg(
+                    Arg::new("dbc")
+                        .long("dbc")
+                        .value_name("file")
+                        .help("Sets the DBC database to decode with")
+                        .required(true),
+                ),
+        );
+    #[cfg(all(feature = "socketcan", target_os = "linux"))]
+    let command = command.subcommand(replay_command());
+    command
+}
+
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+fn replay_command() -> Command {
+    Command::new("replay")
+        .about("Sends the frames of a log onto a CAN interface at the pace they were logged at")
+        .arg(Arg::new("FILE").required(true).index(1))
+        .arg(
+            Arg::new("iface")
+                .long("iface")
+                .value_name("name")
+                .help("Sets the interface to send on, e.g. vcan0")
+                .required(true),
+        )
+        .arg(
+            Arg::new("loop")
+                .long("loop")
+                .help("Starts over at the end of the log until interrupted")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("speed")
+                .long("speed")
+                .value_name("factor")
+                .help("Replays faster or slower than logged, e.g. 2 for double speed")
+                .value_parser(parse_speed)
+                .default_value("1"),
+        )
+}
 
-    let input_file = File::open(args.value_of("INPUT").unwrap()).expect("TODO: remove this unwrap");
-    let parser = AscParser::new(input_file);
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+fn parse_speed(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        Ok(_) => Err(format!("invalid speed {:?}: must be positive", s)),
+        Err(err) => Err(format!("invalid speed {:?}: {}", s, err)),
+    }
+}
+
+/// Sends the frames of the log onto the interface, exiting on the first frame failing to send
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+fn run_replay(path: &str, iface: &str, repeat: bool, speed: f64) {
+    use ascdump::replay;
+    use socketcan::{CanFdSocket, Socket};
+
+    let mut socket = match CanFdSocket::open(iface) {
+        Ok(socket) => socket,
+        Err(err) => {
+            eprintln!("error: cannot open {}: {}", iface, err);
+            process::exit(EXIT_OPEN_FAILURE);
+        }
+    };
+    let read = || -> Box<dyn Iterator<Item = CanFrame>> {
+        match open_input(path, false, false) {
+            Ok(input) => Box::new(AscParser::new(decompress(input, path.ends_with(".gz")))),
+            Err(err) => {
+                eprintln!("error: cannot open {}: {}", path, err);
+                process::exit(EXIT_OPEN_FAILURE);
+            }
+        }
+    };
+
+    // Looping reads the log once and keeps its frames
+    let sent = match repeat {
+        true => {
+            let frames: Vec<CanFrame> = read().collect();
+            // Without frames every pass returns at once, there is nothing to loop over
+            if frames.is_empty() {
+                return;
+            }
+            loop {
+                if let Err(err) = replay(frames.iter().cloned(), &mut socket, speed) {
+                    break Err(err);
+                }
+            }
+        }
+        false => replay(read(), &mut socket, speed),
+    };
+    if let Err(err) = sent {
+        eprintln!("error: cannot send on {}: {}", iface, err);
+        process::exit(EXIT_SEND_FAILURE);
+    }
+}
+
+fn main() {
+    let mut command = command();
+    let args = command.get_matches_mut();
+
+    #[cfg(all(feature = "socketcan", target_os = "linux"))]
+    if let Some(("replay", replay_args)) = args.subcommand() {
+        run_replay(
+            replay_args
+                .get_one::<String>("FILE")
+                .map(String::as_str)
+                .unwrap_or("-"),
+            replay_args
+                .get_one::<String>("iface")
+                .map(String::as_str)
+                .unwrap_or_default(),
+            replay_args.get_flag("loop"),
+            replay_args.get_one::<f64>("speed").copied().unwrap_or(1.0),
+        );
+        return;
+    }
+
+    if let Some(("signals", signals_args)) = args.subcommand() {
+        if cfg!(not(feature = "dbc")) {
+            command
+                .error(
+                    ErrorKind::InvalidValue,
+                    "signals requires ascdump to be built with the dbc feature",
+                )
+                .exit();
+        }
+        let dbc_path = signals_args
+            .get_one::<String>("dbc")
+            .map(String::as_str)
+            .unwrap_or_default();
+        let dbc = match read_dbc(dbc_path) {
+            Ok(dbc) => dbc,
+            Err(err) => {
+                eprintln!("error: {}: {}", dbc_path, err);
+                process::exit(EXIT_OPEN_FAILURE);
+            }
+        };
+        let path = signals_args
+            .get_one::<String>("FILE")
+            .map(String::as_str)
+            .unwrap_or("-");
+        match open_input(path, false, false) {
+            Ok(input) => {
+                let frames = AscParser::new(decompress(input, path.ends_with(".gz")));
+                print_signal_table(frames, &dbc);
+            }
+            Err(err) => {
+                eprintln!("error: cannot open {}: {}", path, err);
+                process::exit(EXIT_OPEN_FAILURE);
+            }
+        }
+        return;
+    }
+
+    if let Some(("diff", diff_args)) = args.subcommand() {
+        let path = |name: &str| diff_args.get_one::<String>(name).map(String::as_str);
+        print_diff(
+            path("LEFT").unwrap_or("-"),
+            path("RIGHT").unwrap_or("-"),
+            diff_args.get_flag("verbose"),
+        );
+        return;
+    }
+
+    let mut ids: HashSet<u32> = args
+        .get_many::<u32>("id")
+        .map(|values| values.copied().collect())
+        .unwrap_or_default();
+    let id_file = |name: &str| {
+        args.get_one::<String>(name)
+            .map(|path| match read_id_file(path) {
+                Ok(ids) => ids,
+                Err(err) => {
+                    eprintln!("error: {}", err);
+                    process::exit(EXIT_OPEN_FAILURE);
+                }
+            })
+            .unwrap_or_default()
+    };
+    // An id file without any ids filters out all frames
+    let filter_ids = args.contains_id("id") || args.contains_id("id-file");
+    ids.extend(id_file("id-file"));
+    let excluded_ids = id_file("exclude-id-file");
+    let buses: Vec<u16> = args
+        .get_many::<u16>("bus")
+        .map(|values| values.copied().collect())
+        .unwrap_or_default();
+    let mapping: HashMap<u16, u16> = args
+        .get_many::<(u16, u16)>("remap")
+        .map(|values| values.copied().collect())
+        .unwrap_or_default();
+    let matches: Vec<PayloadMatch> = args
+        .get_many::<PayloadMatch>("match")
+        .map(|values| values.copied().collect())
+        .unwrap_or_default();
+    let from = args
+        .get_one::<f64>("from")
+        .copied()
+        .unwrap_or(f64::NEG_INFINITY);
+    let to = args.get_one::<f64>("to").copied().unwrap_or(f64::INFINITY);
+    if from > to {
+        command
+            .error(
+                ErrorKind::ValueValidation,
+                "--from must not be greater than --to",
+            )
+            .exit();
+    }
+
+    let format = match args.get_one::<String>("format").map(String::as_str) {
+        Some("json") => Format::Json,
+        Some("csv") => Format::Csv,
+        Some("pcap") => Format::Pcap,
+        Some("candumplog") => Format::CandumpLog,
+        _ => Format::Debug,
+    };
+    let payload_format = match args.get_one::<String>("payload-format").map(String::as_str) {
+        Some("hex") => Some(PayloadFormat::Hex),
+        Some("dec") => Some(PayloadFormat::Dec),
+        Some("bin") => Some(PayloadFormat::Bin),
+        _ => None,
+    };
+    if cfg!(not(feature = "serde")) && matches!(format, Format::Json) {
+        command
+            .error(
+                ErrorKind::InvalidValue,
+                "json output requires ascdump to be built with the serde feature",
+            )
+            .exit();
+    }
+
+    let paths: Vec<&str> = args
+        .get_many::<String>("INPUT")
+        .map(|values| values.map(String::as_str).collect())
+        .unwrap_or_else(|| vec!["-"]);
+    let follow = args.get_flag("follow");
+    if follow && paths.len() > 1 {
+        command
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--follow takes a single input file",
+            )
+            .exit();
+    }
+    let force_gzip = args.get_flag("gzip");
+    let gzip = |path: &str| force_gzip || path.ends_with(".gz");
+    if cfg!(not(feature = "flate2")) && paths.iter().any(|path| gzip(path)) {
+        command
+            .error(
+                ErrorKind::InvalidValue,
+                "gzip input requires ascdump to be built with the flate2 feature",
+            )
+            .exit();
+    }
+
+    let mmap = args.get_flag("mmap");
+    if cfg!(not(feature = "memmap2")) && mmap {
+        command
+            .error(
+                ErrorKind::InvalidValue,
+                "--mmap requires ascdump to be built with the memmap2 feature",
+            )
+            .exit();
+    }
+
+    let absolute_time = args.get_flag("absolute-time");
+    if cfg!(not(feature = "chrono")) && absolute_time {
+        command
+            .error(
+                ErrorKind::InvalidValue,
+                "--absolute-time requires ascdump to be built with the chrono feature",
+            )
+            .exit();
+    }
+
+    let dbc_path = args.get_one::<String>("dbc");
+    if cfg!(not(feature = "dbc")) && dbc_path.is_some() {
+        command
+            .error(
+                ErrorKind::InvalidValue,
+                "--dbc requires ascdump to be built with the dbc feature",
+            )
+            .exit();
+    }
+    let output_path = args.get_one::<String>("output").map(String::as_str);
+    let compression = match args.get_one::<String>("compress").map(String::as_str) {
+        Some("gzip") => Some(Compression::Gzip),
+        Some("zstd") => Some(Compression::Zstd),
+        _ => match output_path {
+            Some(path) if path.ends_with(".gz") => Some(Compression::Gzip),
+            Some(path) if path.ends_with(".zst") => Some(Compression::Zstd),
+            _ => None,
+        },
+    };
+    if cfg!(not(feature = "flate2")) && matches!(compression, Some(Compression::Gzip)) {
+        command
+            .error(
+                ErrorKind::InvalidValue,
+                "gzip output requires ascdump to be built with the flate2 feature",
+            )
+            .exit();
+    }
+    if cfg!(not(feature = "zstd")) && matches!(compression, Some(Compression::Zstd)) {
+        command
+            .error(
+                ErrorKind::InvalidValue,
+                "zstd output requires ascdump to be built with the zstd feature",
+            )
+            .exit();
+    }
+
+    let dbc = dbc_path.map(|path| match read_dbc(path) {
+        Ok(dbc) => dbc,
+        Err(err) => {
+            eprintln!("error: {}: {}", path, err);
+            process::exit(EXIT_OPEN_FAILURE);
+        }
+    });
+
+    // All inputs are opened up front, so nothing is printed if any of them is missing
+    let inputs: Vec<(&str, Box<dyn Read>)> = paths
+        .into_iter()
+        .map(|path| match open_input(path, follow, mmap) {
+            Ok(input) => (path, input),
+            Err(err) => {
+                eprintln!("error: cannot open {}: {}", path, err);
+                process::exit(EXIT_OPEN_FAILURE);
+            }
+        })
+        .collect();
+    let mut out = match Output::open(output_path, compression) {
+        Ok(out) => out,
+        Err(err) => {
+            eprintln!(
+                "error: cannot create {}: {}",
+                output_path.unwrap_or("-"),
+                err
+            );
+            process::exit(EXIT_OPEN_FAILURE);
+        }
+    };
+    let written = |result: io::Result<()>| {
+        if let Err(err) = result {
+            eprintln!("error: cannot write output: {}", err);
+            process::exit(EXIT_OPEN_FAILURE);
+        }
+    };
+
+    if args.get_flag("count") {
+        let count: usize = inputs
+            .into_iter()
+            .map(|(path, input)| count_frames(decompress(input, gzip(path))))
+            .sum();
+        written(writeln!(out, "{}", count));
+        written(out.finish());
+        return;
+    }
+
+    let strict = args.get_flag("strict");
+    let quiet = args.get_flag("quiet");
+    let failed = Cell::new(false);
+    // Bad lines are skipped with a warning unless strict, either way the exit code tells
+    let report = |path: &str, error: LocatedError| {
+        failed.set(true);
+        let path = if path == "-" { "<stdin>" } else { path };
+        let level = if strict { "error" } else { "warning" };
+        if !quiet {
+            eprintln!("{}: {}:{}: {}", level, path, error.line, error.source);
+        }
+        if strict {
+            process::exit(EXIT_PARSE_FAILURE);
+        }
+    };
+
+    // Whether the frames of the file being read are shifted to seconds since the Unix epoch.
+    // Frames are printed as they are read, so this applies to the frame printed last.
+    let dated = Rc::new(Cell::new(false));
+
+    // Every file gets its own parser, so headers are detected per file. Frames are time-ordered
+    // within a file, so parsing can stop at the first frame past the time range.
+    let frames = inputs.into_iter().flat_map(|(path, input)| {
+        let report = &report;
+        let mut parser = AscParser::new(decompress(input, gzip(path))).try_frames();
+        // pcapng records wall-clock time, which is known once the header has been read
+        let offset = Rc::new(Cell::new(0.0));
+        let header_offset = Rc::clone(&offset);
+        let mut offset_known = !absolute_time && !matches!(format, Format::Pcap);
+        let dated = Rc::clone(&dated);
+        iter::from_fn(move || {
+            let result = parser.next()?;
+            if !offset_known && result.is_ok() {
+                let start = start_offset(parser.header());
+                if start.is_none() && absolute_time {
+                    let path = if path == "-" { "<stdin>" } else { path };
+                    eprintln!(
+                        "warning: {} declares no date, printing relative timestamps",
+                        path
+                    );
+                }
+                header_offset.set(start.unwrap_or(0.0));
+                dated.set(start.is_some());
+                offset_known = true;
+            }
+            Some(result)
+        })
+        .filter_map(move |result| result.map_err(|error| report(path, error)).ok())
+        .skip_while(move |frame| frame.timestamp < from)
+        .take_while(move |frame| frame.timestamp <= to)
+        .map(move |mut frame| {
+            frame.timestamp += offset.get();
+            frame
+        })
+    });
+    let frames = remap_bus(frames, mapping)
+        .filter(|frame| !filter_ids || ids.contains(&frame.id))
+        .filter(|frame| !excluded_ids.contains(&frame.id))
+        .filter(|frame| buses.is_empty() || buses.contains(&frame.bus_id))
+        .filter(|frame| {
+            matches
+                .iter()
+                .all(|payload_match| payload_match.matches(frame))
+        });
+
+    if args.get_flag("stats") {
+        let bitrates: HashMap<u16, u32> = args
+            .get_many::<(u16, u32)>("bitrate")
+            .map(|values| values.copied().collect())
+            .unwrap_or_default();
+        // Frames are only buffered if the bus load is needed
+        let mut buffered = vec![];
+        let mut stats = Stats::default();
+        for frame in frames {
+            stats.add(&frame);
+            if !bitrates.is_empty() {
+                buffered.push(frame);
+            }
+        }
+        written(stats.print(&mut out));
+        if !bitrates.is_empty() {
+            written(print_bus_load(&mut out, &buffered, &bitrates));
+        }
+    } else if args.get_flag("histogram") {
+        written(print_histogram(&mut out, frames));
+    } else if let Some(dbc) = &dbc {
+        for frame in frames {
+            written(print_signals(&mut out, &frame, dbc));
+        }
+    } else if let Format::Pcap = format {
+        written(write_pcap(&mut out, frames));
+    } else {
+        if let Format::Csv = format {
+            written(writeln!(out, "{}", CSV_HEADER));
+        }
+        for frame in frames {
+            let wall_clock = absolute_time && dated.get();
+            written(print_frame(
+                &mut out,
+                &frame,
+                format,
+                payload_format,
+                wall_clock,
+            ));
+        }
+    }
+    written(out.finish());
 
-    for frame in parser.filter(|frame| frame.id == 2000) {
-        println!("{:?}", frame);
+    if failed.get() {
+        process::exit(EXIT_PARSE_FAILURE);
     }
 }
@@ -0,0 +1,135 @@
+use crate::{dlc_to_length, CanFrame, Direction};
+
+/// Builds a [`CanFrame`] field by field, see [`CanFrame::builder`]
+///
+/// Fields that are not set default to a received classic data frame on bus 0 with id 0 and
+/// an empty payload.
+#[derive(Debug)]
+pub struct CanFrameBuilder {
+    frame: CanFrame,
+}
+
+impl CanFrameBuilder {
+    pub fn timestamp(mut self, timestamp: f64) -> Self {
+        self.frame.timestamp = timestamp;
+        self
+    }
+
+    pub fn bus_id(mut self, bus_id: u16) -> Self {
+        self.frame.bus_id = bus_id;
+        self
+    }
+
+    pub fn id(mut self, id: u32) -> Self {
+        self.frame.id = id;
+        self
+    }
+
+    pub fn extended(mut self, is_extended: bool) -> Self {
+        self.frame.is_extended = is_extended;
+        self
+    }
+
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.frame.direction = direction;
+        self
+    }
+
+    /// Marks the frame as CAN FD frame, which is implied for payloads longer than 8 bytes
+    pub fn fd(mut self, is_fd: bool) -> Self {
+        self.frame.is_fd = is_fd;
+        self
+    }
+
+    pub fn payload(mut self, payload: &[u8]) -> Self {
+        self.frame.payload = payload.to_vec();
+        self
+    }
+
+    /// Finishes the frame, deriving its length and DLC from the payload
+    ///
+    /// Payloads longer than 8 bytes get the smallest CAN FD DLC covering them.
+    pub fn build(mut self) -> CanFrame {
+        self.frame.length = self.frame.payload.len();
+        self.frame.is_fd |= self.frame.length > 8;
+        self.frame.dlc = (0..=15)
+            .find(|dlc| dlc_to_length(*dlc) >= self.frame.length)
+            .unwrap_or(15);
+        self.frame
+    }
+}
+
+impl CanFrame {
+    /// Starts building a frame, e.g. for tests or replay tools
+    ///
+    /// ```
+    /// use ascdump::CanFrame;
+    ///
+    /// let frame = CanFrame::builder()
+    ///     .bus_id(1)
+    ///     .id(0x123)
+    ///     .payload(&[0x11, 0x22])
+    ///     .build();
+    /// assert_eq!(frame.length, 2);
+    /// ```
+    pub fn builder() -> CanFrameBuilder {
+        CanFrameBuilder {
+            frame: CanFrame::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn build_standard_frame() {
+        let frame = CanFrame::builder()
+            .timestamp(0.962604)
+            .bus_id(3)
+            .id(0x368)
+            .payload(&[0xCC, 0x55, 0x01, 0x00])
+            .build();
+
+        assert_eq!(frame.timestamp, 0.962604);
+        assert_eq!(frame.bus_id, 3);
+        assert_eq!(frame.id, 0x368);
+        assert!(!frame.is_extended);
+        assert_eq!(frame.direction, Direction::Rx);
+        assert!(!frame.is_rtr);
+        assert_eq!(frame.dlc, 4);
+        assert_eq!(frame.length, 4);
+        assert_eq!(frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn build_extended_frame() {
+        let frame = CanFrame::builder()
+            .bus_id(3)
+            .id(0x1F78C410)
+            .extended(true)
+            .direction(Direction::Tx)
+            .payload(&[0x02, 0x00, 0x00, 0x00, 0x24, 0x00, 0x70, 0x03])
+            .build();
+
+        assert_eq!(frame.id, 0x1F78C410);
+        assert!(frame.is_extended);
+        assert_eq!(frame.direction, Direction::Tx);
+        assert_eq!(frame.dlc, 8);
+        assert_eq!(frame.length, 8);
+    }
+
+    #[test]
+    fn build_frame_with_fd_payload() {
+        let frame = CanFrame::builder().payload(&[0xAA; 20]).build();
+        assert!(frame.is_fd);
+        assert_eq!(frame.length, 20);
+        assert_eq!(frame.dlc, 11);
+
+        let frame = CanFrame::builder().payload(&[0xAA; 13]).build();
+        assert_eq!(frame.length, 13);
+        assert_eq!(frame.dlc, 10);
+    }
+}
@@ -0,0 +1,190 @@
+use std::convert::TryFrom;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use can_dbc::{ByteOrder, Dbc, Message, MessageId, MultiplexIndicator, Signal, ValueType};
+use thiserror::Error;
+
+use crate::CanFrame;
+
+#[derive(Error, Debug)]
+pub enum DbcReadError {
+    #[error("Cannot read DBC file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Cannot parse DBC file: {0}")]
+    Parse(#[from] can_dbc::DbcError),
+}
+
+/// Reads and parses a DBC file
+pub fn read_dbc<P: AsRef<Path>>(path: P) -> Result<Dbc, DbcReadError> {
+    let content = fs::read_to_string(path)?;
+    Ok(Dbc::try_from(content.as_str())?)
+}
+
+impl CanFrame {
+    /// Decodes the signals of the frame as defined for its id in the DBC database
+    ///
+    /// Returns the physical value of every signal by name, in the order of the database.
+    /// Multiplexed signals are only decoded if the multiplexor selects them. Frames unknown to
+    /// the database and signals beyond the payload yield no values.
+    pub fn decode(&self, dbc: &Dbc) -> Vec<(String, f64)> {
+        let message = match find_message(dbc, self) {
+            Some(message) => message,
+            None => return vec![],
+        };
+
+        let multiplexor = message
+            .signals
+            .iter()
+            .find(|signal| signal.multiplexer_indicator == MultiplexIndicator::Multiplexor)
+            .and_then(|signal| raw_value(signal, &self.payload));
+
+        message
+            .signals
+            .iter()
+            .filter(|signal| match signal.multiplexer_indicator {
+                MultiplexIndicator::MultiplexedSignal(value)
+                | MultiplexIndicator::MultiplexorAndMultiplexedSignal(value) => {
+                    multiplexor == Some(value)
+                }
+                _ => true,
+            })
+            .filter_map(|signal| {
+                let raw = raw_value(signal, &self.payload)?;
+                let raw = match signal.value_type {
+                    ValueType::Signed => sign_extend(raw, signal.size) as f64,
+                    ValueType::Unsigned => raw as f64,
+                };
+                Some((signal.name.clone(), raw * signal.factor + signal.offset))
+            })
+            .collect()
+    }
+}
+
+fn find_message<'a>(dbc: &'a Dbc, frame: &CanFrame) -> Option<&'a Message> {
+    let id = match frame.is_extended {
+        true => MessageId::Extended(frame.id),
+        false => MessageId::Standard(u16::try_from(frame.id).ok()?),
+    };
+    dbc.messages.iter().find(|message| message.id == id)
+}
+
+/// Extracts the raw bits of a signal, `None` if they exceed the payload
+///
+/// Little endian signals start at their least significant bit. Big endian signals start at their
+/// most significant bit and continue towards lower bit positions, wrapping into the next byte.
+fn raw_value(signal: &Signal, payload: &[u8]) -> Option<u64> {
+    if signal.size == 0 || signal.size > 64 {
+        return None;
+    }
+    let bit = |position: u64| -> Option<u64> {
+        let byte = payload.get((position / 8) as usize)?;
+        Some(((byte >> (position % 8)) & 1) as u64)
+    };
+
+    let mut raw = 0;
+    match signal.byte_order {
+        ByteOrder::LittleEndian => {
+            for i in 0..signal.size {
+                raw |= bit(signal.start_bit + i)? << i;
+            }
+        }
+        ByteOrder::BigEndian => {
+            let mut position = signal.start_bit;
+            for _ in 0..signal.size {
+                raw = raw << 1 | bit(position)?;
+                position = match position % 8 {
+                    0 => position + 15,
+                    _ => position - 1,
+                };
+            }
+        }
+    }
+    Some(raw)
+}
+
+fn sign_extend(raw: u64, size: u64) -> i64 {
+    let shift = 64 - size;
+    ((raw << shift) as i64) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::str::FromStr;
+
+    fn sample_dbc() -> Dbc {
+        read_dbc(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/sample.dbc"
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn decode_signals() {
+        let dbc = sample_dbc();
+        let frame = CanFrame::from_str(
+            "0.962604 3 368 Rx d 4 cc 55 01 fe Length = 0 BitCount = 0 ID = 872",
+        )
+        .unwrap();
+
+        assert_eq!(
+            frame.decode(&dbc),
+            vec![
+                (String::from("Mode"), 204.0),
+                (String::from("Speed"), 160.5),
+                (String::from("Temperature"), 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_big_endian_and_signed_signals() {
+        let dbc = sample_dbc();
+        let frame =
+            CanFrame::from_str("0.962892 3 1f78c410x Rx d 8 02 00 00 00 24 01 70 03").unwrap();
+        assert_eq!(frame.decode(&dbc), vec![(String::from("Counter"), 9217.0)]);
+
+        let frame = CanFrame::builder()
+            .id(0x368)
+            .payload(&[0, 0, 0, 0xFE])
+            .build();
+        assert!(frame
+            .decode(&dbc)
+            .contains(&(String::from("Temperature"), 0.0)));
+        let frame = CanFrame::builder().id(0x368).payload(&[0, 0, 0xFE]).build();
+        assert!(frame
+            .decode(&dbc)
+            .contains(&(String::from("Temperature"), -2.0)));
+    }
+
+    #[test]
+    fn skip_unknown_frames_and_short_payloads() {
+        let dbc = sample_dbc();
+        let unknown = CanFrame::builder().id(0x123).payload(&[1, 2]).build();
+        assert!(unknown.decode(&dbc).is_empty());
+
+        // Standard and extended ids are told apart
+        let extended = CanFrame::builder()
+            .id(0x368)
+            .extended(true)
+            .payload(&[1, 2, 3, 4])
+            .build();
+        assert!(extended.decode(&dbc).is_empty());
+
+        let short = CanFrame::builder().id(0x368).payload(&[0xCC, 0x55]).build();
+        assert_eq!(short.decode(&dbc), vec![(String::from("Mode"), 204.0)]);
+    }
+
+    #[test]
+    fn fail_on_missing_dbc() {
+        assert!(matches!(
+            read_dbc("does/not/exist.dbc"),
+            Err(DbcReadError::Io(_))
+        ));
+    }
+}
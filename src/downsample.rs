@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use crate::CanFrame;
+
+/// An iterator yielding at most one frame per id and interval, see
+/// [`crate::AscParser::downsample_by_time`]
+pub struct DownsampleByTime<I> {
+    frames: I,
+    interval: f64,
+    last: HashMap<(u32, bool), f64>,
+}
+
+impl<I> DownsampleByTime<I>
+where
+    I: Iterator<Item = CanFrame>,
+{
+    /// Downsamples the frames of any iterator
+    pub fn new(frames: I, interval: f64) -> Self {
+        Self {
+            frames,
+            interval,
+            last: HashMap::new(),
+        }
+    }
+}
+
+impl<I> Iterator for DownsampleByTime<I>
+where
+    I: Iterator<Item = CanFrame>,
+{
+    type Item = CanFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.frames.next()?;
+            let key = (frame.id, frame.is_extended);
+            let is_due = match self.last.get(&key) {
+                Some(last) => frame.timestamp - last >= self.interval,
+                None => true,
+            };
+            if is_due {
+                self.last.insert(key, frame.timestamp);
+                return Some(frame);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::{AscParser, CanFrame};
+
+    fn frames() -> String {
+        // Id 100 is sent every 10 ms and id 200 every 50 ms for half a second
+        let mut lines = String::new();
+        for i in 0..50 {
+            let timestamp = i as f64 * 0.01;
+            lines += &format!("{:.6} 1 100 Rx d 1 {:02x}\n", timestamp, i);
+            if i % 5 == 0 {
+                lines += &format!("{:.6} 1 200 Rx d 1 {:02x}\n", timestamp, i);
+            }
+        }
+        lines
+    }
+
+    #[test]
+    fn downsample_by_time() {
+        let input = frames();
+        let frames: Vec<CanFrame> = AscParser::new(input.as_bytes())
+            .downsample_by_time(0.1)
+            .collect();
+
+        let count = |id: u32| frames.iter().filter(|frame| frame.id == id).count();
+        assert_eq!(count(0x100), 5);
+        assert_eq!(count(0x200), 5);
+        assert_eq!(frames[0].timestamp, 0.0);
+    }
+
+    #[test]
+    fn downsample_by_nth() {
+        let input = frames();
+        let frames: Vec<CanFrame> = AscParser::new(input.as_bytes())
+            .downsample_by_nth(6)
+            .collect();
+
+        assert_eq!(frames.len(), 10);
+        assert_eq!(frames[1].timestamp, 0.05);
+    }
+}
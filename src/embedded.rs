@@ -0,0 +1,150 @@
+use std::convert::TryFrom;
+
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+
+use crate::CanFrame;
+
+/// Frames parsed from a log are not range checked, so [`Frame::id`] panics for standard ids
+/// beyond `0x7FF` and extended ids beyond `0x1FFFFFFF`
+impl Frame for CanFrame {
+    fn new(id: impl Into<Id>, data: &[u8]) -> Option<Self> {
+        if data.len() > 8 {
+            return None;
+        }
+
+        let mut frame = CanFrame::new();
+        frame.set_id(id.into());
+        frame.dlc = data.len() as u8;
+        frame.length = data.len();
+        frame.payload = data.to_vec();
+        Some(frame)
+    }
+
+    fn new_remote(id: impl Into<Id>, dlc: usize) -> Option<Self> {
+        if dlc > 8 {
+            return None;
+        }
+
+        let mut frame = CanFrame::new();
+        frame.set_id(id.into());
+        frame.is_rtr = true;
+        frame.dlc = dlc as u8;
+        frame.length = dlc;
+        Some(frame)
+    }
+
+    fn is_extended(&self) -> bool {
+        self.is_extended
+    }
+
+    fn is_remote_frame(&self) -> bool {
+        self.is_rtr
+    }
+
+    fn id(&self) -> Id {
+        // Masking the id would report a different message, so out of range ids are fatal
+        match self.is_extended {
+            true => ExtendedId::new(self.id).map(Id::Extended),
+            false => u16::try_from(self.id)
+                .ok()
+                .and_then(StandardId::new)
+                .map(Id::Standard),
+        }
+        .unwrap_or_else(|| {
+            panic!(
+                "Identifier {:#x} out of range of {} frame",
+                self.id,
+                if self.is_extended {
+                    "an extended"
+                } else {
+                    "a standard"
+                }
+            )
+        })
+    }
+
+    fn dlc(&self) -> usize {
+        self.dlc as usize
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.payload
+    }
+}
+
+impl CanFrame {
+    fn set_id(&mut self, id: Id) {
+        match id {
+            Id::Standard(id) => {
+                self.id = id.as_raw() as u32;
+                self.is_extended = false;
+            }
+            Id::Extended(id) => {
+                self.id = id.as_raw();
+                self.is_extended = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn id_of_standard_frame() {
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(
+            Frame::id(&frame),
+            Id::Standard(StandardId::new(0x368).unwrap())
+        );
+        assert_eq!(Frame::dlc(&frame), 4);
+        assert_eq!(frame.data(), &[0xCC, 0x55, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn id_of_extended_frame() {
+        let line =
+            String::from("0.962892 3 1f78c410x Rx d 8 02 00 00 00 24 00 70 03 Length = 0 BitCount = 0 ID = 528008208x");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(
+            Frame::id(&frame),
+            Id::Extended(ExtendedId::new(0x1f78c410).unwrap())
+        );
+        assert!(Frame::is_extended(&frame));
+    }
+
+    #[test]
+    #[should_panic(expected = "Identifier 0x7ff0 out of range of a standard frame")]
+    fn panic_on_out_of_range_id() {
+        let line =
+            String::from("0.962604 3 7ff0 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        Frame::id(&frame);
+    }
+
+    #[test]
+    fn construct_frames() {
+        let frame = <CanFrame as Frame>::new(StandardId::new(0x123).unwrap(), &[1, 2, 3])
+            .expect("Failed to construct data frame");
+        assert_eq!(frame.id, 0x123);
+        assert!(!frame.is_extended);
+        assert_eq!(frame.length, 3);
+        assert_eq!(frame.payload, vec![1, 2, 3]);
+
+        let frame = <CanFrame as Frame>::new_remote(ExtendedId::new(0x1234567).unwrap(), 8)
+            .expect("Failed to construct remote frame");
+        assert_eq!(frame.id, 0x1234567);
+        assert!(frame.is_extended);
+        assert!(frame.is_remote_frame());
+        assert_eq!(Frame::dlc(&frame), 8);
+        assert!(frame.data().is_empty());
+
+        assert!(<CanFrame as Frame>::new(StandardId::ZERO, &[0; 9]).is_none());
+        assert!(<CanFrame as Frame>::new_remote(StandardId::ZERO, 9).is_none());
+    }
+}
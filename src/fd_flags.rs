@@ -0,0 +1,75 @@
+use std::fmt;
+use std::ops::BitOr;
+
+/// The `Flags` field of a CANFD line, a bitfield written in hex, e.g. `3000`
+///
+/// The field packs the frame format flags together with flags of the logging hardware. All bits
+/// are kept, the known ones are available as constants and accessors. Unlike the separate BRS
+/// and ESI columns, which are `0` or `1` tokens, the bit positions in this field have been the
+/// same across versions of the logging tools.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FdFlags(pub u32);
+
+impl FdFlags {
+    /// Extended data length, set for frames in CAN FD format
+    pub const EDL: FdFlags = FdFlags(0x1000);
+    /// Bit rate switch, the data phase was sent at the higher bit rate
+    pub const BRS: FdFlags = FdFlags(0x2000);
+    /// Error state indicator, the sender was error passive
+    pub const ESI: FdFlags = FdFlags(0x4000);
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Whether all bits set in `other` are set as well
+    pub fn contains(self, other: FdFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn edl(self) -> bool {
+        self.contains(Self::EDL)
+    }
+
+    pub fn brs(self) -> bool {
+        self.contains(Self::BRS)
+    }
+
+    pub fn esi(self) -> bool {
+        self.contains(Self::ESI)
+    }
+}
+
+impl BitOr for FdFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        FdFlags(self.0 | other.0)
+    }
+}
+
+/// Renders the flags in hex as written in CANFD lines
+impl fmt::Display for FdFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn combine_flags() {
+        let flags = FdFlags::EDL | FdFlags::BRS;
+        assert_eq!(flags, FdFlags(0x3000));
+        assert!(flags.edl());
+        assert!(flags.brs());
+        assert!(!flags.esi());
+        assert!(flags.contains(FdFlags::EDL));
+        assert!(!flags.contains(FdFlags::EDL | FdFlags::ESI));
+        assert_eq!(flags.to_string(), "3000");
+    }
+}
@@ -0,0 +1,96 @@
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use crate::AscParser;
+
+/// A log file opened by [`AscParser::open`], decompressed while reading if gzipped
+#[derive(Debug)]
+pub enum AscFile {
+    Plain(File),
+    #[cfg(feature = "flate2")]
+    Gzip(flate2::read::MultiGzDecoder<File>),
+}
+
+impl Read for AscFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            AscFile::Plain(file) => file.read(buf),
+            #[cfg(feature = "flate2")]
+            AscFile::Gzip(decoder) => decoder.read(buf),
+        }
+    }
+}
+
+impl AscParser<AscFile> {
+    /// Opens the log file at the given path and creates a parser for it
+    ///
+    /// With the flate2 feature, files ending in `.gz` are decompressed while parsing.
+    ///
+    /// ```no_run
+    /// use ascdump::AscParser;
+    ///
+    /// let frames = AscParser::open("measurement.asc")?.count();
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let input = match path.extension() {
+            #[cfg(feature = "flate2")]
+            Some(extension) if extension == "gz" => {
+                AscFile::Gzip(flate2::read::MultiGzDecoder::new(file))
+            }
+            _ => AscFile::Plain(file),
+        };
+        Ok(Self::new(input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::io::Write;
+
+    const LINES: &str = "base hex  timestamps absolute\n\
+                         0.1 1 101 Rx d 1 01 Length = 0 BitCount = 0 ID = 257\n\
+                         0.2 1 102 Rx d 1 02 Length = 0 BitCount = 0 ID = 258\n";
+
+    #[test]
+    fn open_file_by_path() {
+        let path = std::env::temp_dir().join(format!("ascdump-open-{}.asc", std::process::id()));
+        File::create(&path)
+            .and_then(|mut file| file.write_all(LINES.as_bytes()))
+            .expect("Failed to write temp file");
+
+        let ids: Vec<u32> = AscParser::open(&path)
+            .expect("Failed to open temp file")
+            .map(|frame| frame.id)
+            .collect();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(ids, vec![0x101, 0x102]);
+
+        assert!(AscParser::open(&path).is_err());
+    }
+
+    #[cfg(feature = "flate2")]
+    #[test]
+    fn open_gzipped_file_by_path() {
+        let path = std::env::temp_dir().join(format!("ascdump-open-{}.asc.gz", std::process::id()));
+        let file = File::create(&path).expect("Failed to create temp file");
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        encoder
+            .write_all(LINES.as_bytes())
+            .and_then(|_| encoder.finish())
+            .expect("Failed to write temp file");
+
+        let ids: Vec<u32> = AscParser::open(&path)
+            .expect("Failed to open temp file")
+            .map(|frame| frame.id)
+            .collect();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(ids, vec![0x101, 0x102]);
+    }
+}
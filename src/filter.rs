@@ -0,0 +1,103 @@
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::CanFrame;
+
+/// Selects frames by the value of a payload byte, optionally comparing only the bits of a mask
+///
+/// Written as `<offset>:<byte>` or `<offset>:<byte>/<mask>` with a decimal offset and hex
+/// values, e.g. `0:cc` or `1:0x40/0xf0`. Frames too short to have a byte at the offset do not
+/// match.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PayloadMatch {
+    pub offset: usize,
+    pub value: u8,
+    pub mask: u8,
+}
+
+impl PayloadMatch {
+    pub fn matches(&self, frame: &CanFrame) -> bool {
+        frame
+            .payload
+            .get(self.offset)
+            .is_some_and(|byte| byte & self.mask == self.value & self.mask)
+    }
+}
+
+#[derive(Error, Debug, PartialEq)]
+#[error("Invalid payload match {str:?}, expected <offset>:<byte>[/<mask>]")]
+pub struct InvalidPayloadMatch {
+    pub str: String,
+}
+
+impl FromStr for PayloadMatch {
+    type Err = InvalidPayloadMatch;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidPayloadMatch { str: s.to_string() };
+        let parse_byte = |t: &str| {
+            let digits = t
+                .strip_prefix("0x")
+                .or_else(|| t.strip_prefix("0X"))
+                .unwrap_or(t);
+            u8::from_str_radix(digits, 16).map_err(|_| invalid())
+        };
+
+        let (offset, byte) = s.split_once(':').ok_or_else(invalid)?;
+        let offset = usize::from_str(offset).map_err(|_| invalid())?;
+        let (value, mask) = match byte.split_once('/') {
+            Some((value, mask)) => (parse_byte(value)?, parse_byte(mask)?),
+            None => (parse_byte(byte)?, 0xFF),
+        };
+        Ok(Self {
+            offset,
+            value,
+            mask,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_payload_match() {
+        assert_eq!(
+            PayloadMatch::from_str("0:cc"),
+            Ok(PayloadMatch {
+                offset: 0,
+                value: 0xCC,
+                mask: 0xFF
+            })
+        );
+        assert_eq!(
+            PayloadMatch::from_str("3:0x40/0xF0"),
+            Ok(PayloadMatch {
+                offset: 3,
+                value: 0x40,
+                mask: 0xF0
+            })
+        );
+        assert!(PayloadMatch::from_str("cc").is_err());
+        assert!(PayloadMatch::from_str("x:cc").is_err());
+        assert!(PayloadMatch::from_str("0:100").is_err());
+        assert!(PayloadMatch::from_str("0:cc/").is_err());
+    }
+
+    #[test]
+    fn match_payload_bytes() {
+        let frame = CanFrame::builder().payload(&[0xCC, 0x55, 0x01]).build();
+        assert!(PayloadMatch::from_str("0:cc").unwrap().matches(&frame));
+        assert!(!PayloadMatch::from_str("1:cc").unwrap().matches(&frame));
+        assert!(PayloadMatch::from_str("1:0x5a/0xf0")
+            .unwrap()
+            .matches(&frame));
+        assert!(!PayloadMatch::from_str("1:0x5a/0x0f")
+            .unwrap()
+            .matches(&frame));
+        assert!(!PayloadMatch::from_str("3:00/00").unwrap().matches(&frame));
+    }
+}
@@ -0,0 +1,144 @@
+use std::str::FromStr;
+
+use crate::{
+    check_payload_length, parse_fd_flags, scan_payload, AscHeader, AscParseError, CanFrame,
+    Direction, FdFlags,
+};
+
+/// Payload storage large enough for any CAN FD frame
+pub type FixedPayload = heapless::Vec<u8, 64>;
+
+/// A frame storing its payload inline instead of on the heap
+///
+/// Parsing a `FixedCanFrame` from a line does not allocate unless the line fails to parse, which
+/// suits consumers that must not allocate per frame. The crate still requires `std` though, the
+/// `heapless` feature is no `no_std` build. The fields are the same as for [`CanFrame`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct FixedCanFrame {
+    pub timestamp: f64,
+    pub bus_id: u16,
+    pub id: u32,
+    pub is_extended: bool,
+    pub direction: Direction,
+    pub is_rtr: bool,
+    pub is_fd: bool,
+    pub brs: bool,
+    pub esi: bool,
+    pub dlc: u8,
+    pub length: usize,
+    pub payload: FixedPayload,
+    pub frame_length_bits: Option<u32>,
+    pub bit_count: Option<u32>,
+    pub fd_flags: Option<FdFlags>,
+}
+
+impl FixedCanFrame {
+    /// Parses a line of an ASC file without a header, i.e. with hex ids and payload
+    pub fn parse_line(line: &str) -> Result<Self, AscParseError> {
+        Self::parse_with_header(line, &AscHeader::default())
+    }
+
+    /// Parses a line using the numeric base declared in the given header
+    pub fn parse_with_header(line: &str, header: &AscHeader) -> Result<Self, AscParseError> {
+        let (frame, payload_text) = CanFrame::parse_fields(line, header)?;
+
+        let mut payload = FixedPayload::new();
+        let mut payload_end = 0;
+        // Remote frames request data, they state a length but carry no payload
+        if !frame.is_rtr {
+            // The length has been checked against the 64 byte maximum, so pushing cannot fail
+            let (count, end) =
+                scan_payload(payload_text, frame.length, header.base.radix(), |b| {
+                    let _ = payload.push(b);
+                })?;
+            check_payload_length(frame.length, count)?;
+            payload_end = end;
+        }
+        let fd_flags = match frame.is_fd {
+            true => parse_fd_flags(&payload_text[payload_end..]),
+            false => None,
+        };
+
+        Ok(Self {
+            timestamp: frame.timestamp,
+            bus_id: frame.bus_id,
+            id: frame.id,
+            is_extended: frame.is_extended,
+            direction: frame.direction,
+            is_rtr: frame.is_rtr,
+            is_fd: frame.is_fd,
+            brs: frame.brs,
+            esi: frame.esi,
+            dlc: frame.dlc,
+            length: frame.length,
+            payload,
+            frame_length_bits: frame.frame_length_bits,
+            bit_count: frame.bit_count,
+            fd_flags,
+        })
+    }
+
+    /// Copies the frame into a [`CanFrame`] with a heap allocated payload
+    pub fn to_owned(&self) -> CanFrame {
+        CanFrame {
+            timestamp: self.timestamp,
+            bus_id: self.bus_id,
+            id: self.id,
+            is_extended: self.is_extended,
+            direction: self.direction,
+            is_rtr: self.is_rtr,
+            is_fd: self.is_fd,
+            brs: self.brs,
+            esi: self.esi,
+            dlc: self.dlc,
+            length: self.length,
+            payload: self.payload.to_vec(),
+            frame_length_bits: self.frame_length_bits,
+            bit_count: self.bit_count,
+            fd_flags: self.fd_flags,
+        }
+    }
+}
+
+impl FromStr for FixedCanFrame {
+    type Err = AscParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_line(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_fixed_frame() {
+        let line = "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872";
+        let frame = FixedCanFrame::parse_line(line).unwrap();
+        assert_eq!(frame.id, 0x368);
+        assert_eq!(&frame.payload[..], &[0xCC, 0x55, 0x01, 0x00]);
+        assert_eq!(frame.to_owned(), CanFrame::from_str(line).unwrap());
+
+        assert_eq!(
+            FixedCanFrame::parse_line("0.962604 3 368 Rx d 4 cc 55 01"),
+            Err(AscParseError::InvalidPayloadLength { exp: 4, act: 3 })
+        );
+    }
+
+    #[test]
+    fn parse_fixed_fd_frame_of_64_bytes() {
+        let payload: Vec<String> = (0..64).map(|byte| format!("{:02x}", byte)).collect();
+        let line = format!(
+            "7.392600 CANFD 1 Rx 6e 1 0 f 64 {} 0 0 3000 0 0 0 0 0",
+            payload.join(" ")
+        );
+        let frame: FixedCanFrame = line.parse().unwrap();
+        assert!(frame.is_fd);
+        assert_eq!(frame.length, 64);
+        assert_eq!(frame.payload.len(), 64);
+        assert_eq!(frame.payload[63], 63);
+        assert_eq!(frame.to_owned(), CanFrame::from_str(&line).unwrap());
+    }
+}
@@ -0,0 +1,82 @@
+use std::io;
+use std::io::Read;
+use std::thread;
+use std::time::Duration;
+
+/// A reader that keeps waiting for more data at end of input, like `tail -f`
+///
+/// Reads block until new data is appended to the underlying reader, polling it in the given
+/// interval. A parser built on top of it treats a partially written line as incomplete and
+/// waits for the rest of it, so it never reaches the end of input.
+pub struct Follow<R: Read> {
+    inner: R,
+    interval: Duration,
+}
+
+impl<R> Follow<R>
+where
+    R: Read,
+{
+    pub fn new(inner: R) -> Self {
+        Self::with_interval(inner, Duration::from_millis(100))
+    }
+
+    pub fn with_interval(inner: R, interval: Duration) -> Self {
+        Self { inner, interval }
+    }
+}
+
+impl<R> Read for Follow<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.inner.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            thread::sleep(self.interval);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::AscParser;
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+
+    #[test]
+    fn pick_up_appended_lines() {
+        let path = std::env::temp_dir().join(format!("ascdump-follow-{}.asc", std::process::id()));
+        File::create(&path).expect("Failed to create temp file");
+
+        let writer_path = path.clone();
+        let writer = thread::spawn(move || {
+            let mut file = OpenOptions::new()
+                .append(true)
+                .open(writer_path)
+                .expect("Failed to open temp file");
+            write!(file, "base hex  timestamps absolute\n0.1 1 101 Rx d 1 01").unwrap();
+            file.flush().unwrap();
+            thread::sleep(Duration::from_millis(50));
+            writeln!(file, " Length = 0 BitCount = 0 ID = 257").unwrap();
+            thread::sleep(Duration::from_millis(50));
+            writeln!(file, "0.2 1 102 Rx d 1 02 Length = 0 BitCount = 0 ID = 258").unwrap();
+        });
+
+        let file = File::open(&path).expect("Failed to open temp file");
+        let follow = Follow::with_interval(file, Duration::from_millis(5));
+        let ids: Vec<u32> = AscParser::new(follow)
+            .take(2)
+            .map(|frame| frame.id)
+            .collect();
+
+        writer.join().expect("Writer thread panicked");
+        std::fs::remove_file(&path).ok();
+        assert_eq!(ids, vec![0x101, 0x102]);
+    }
+}
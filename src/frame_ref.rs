@@ -0,0 +1,205 @@
+use crate::{
+    check_payload_length, parse_fd_flags, scan_payload, AscHeader, AscParseError, CanFrame,
+    Direction, FdFlags,
+};
+
+/// A frame whose payload borrows from the line it was parsed from
+///
+/// Parsing a `CanFrameRef` does not allocate, which suits consumers that process each frame
+/// right away and reuse their line buffer. Use [`CanFrameRef::to_owned`] to keep a frame.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct CanFrameRef<'a> {
+    pub timestamp: f64,
+    pub bus_id: u16,
+    pub id: u32,
+    pub is_extended: bool,
+    pub direction: Direction,
+    pub is_rtr: bool,
+    pub is_fd: bool,
+    pub brs: bool,
+    pub esi: bool,
+    pub dlc: u8,
+    pub length: usize,
+    pub payload: PayloadRef<'a>,
+    pub frame_length_bits: Option<u32>,
+    pub bit_count: Option<u32>,
+    pub fd_flags: Option<FdFlags>,
+}
+
+impl<'a> CanFrameRef<'a> {
+    /// Parses a line of an ASC file without a header, i.e. with hex ids and payload
+    pub fn parse_line(line: &'a str) -> Result<Self, AscParseError> {
+        Self::parse_with_header(line, &AscHeader::default())
+    }
+
+    /// Parses a line using the numeric base declared in the given header
+    pub fn parse_with_header(line: &'a str, header: &AscHeader) -> Result<Self, AscParseError> {
+        let (frame, payload_text) = CanFrame::parse_fields(line, header)?;
+        let radix = header.base.radix();
+
+        let payload = match frame.is_rtr {
+            // Remote frames request data, they state a length but carry no payload
+            true => PayloadRef::empty(radix),
+            false => {
+                let (count, end) = scan_payload(payload_text, frame.length, radix, |_| {})?;
+                check_payload_length(frame.length, count)?;
+                PayloadRef {
+                    text: &payload_text[..end],
+                    len: count,
+                    radix,
+                }
+            }
+        };
+        let fd_flags = match frame.is_fd {
+            true => parse_fd_flags(&payload_text[payload.text.len()..]),
+            false => None,
+        };
+
+        Ok(Self {
+            timestamp: frame.timestamp,
+            bus_id: frame.bus_id,
+            id: frame.id,
+            is_extended: frame.is_extended,
+            direction: frame.direction,
+            is_rtr: frame.is_rtr,
+            is_fd: frame.is_fd,
+            brs: frame.brs,
+            esi: frame.esi,
+            dlc: frame.dlc,
+            length: frame.length,
+            payload,
+            frame_length_bits: frame.frame_length_bits,
+            bit_count: frame.bit_count,
+            fd_flags,
+        })
+    }
+
+    /// Copies the frame into an owned [`CanFrame`], decoding the payload
+    pub fn to_owned(&self) -> CanFrame {
+        CanFrame {
+            timestamp: self.timestamp,
+            bus_id: self.bus_id,
+            id: self.id,
+            is_extended: self.is_extended,
+            direction: self.direction,
+            is_rtr: self.is_rtr,
+            is_fd: self.is_fd,
+            brs: self.brs,
+            esi: self.esi,
+            dlc: self.dlc,
+            length: self.length,
+            payload: self.payload.to_vec(),
+            frame_length_bits: self.frame_length_bits,
+            bit_count: self.bit_count,
+            fd_flags: self.fd_flags,
+        }
+    }
+}
+
+/// The payload bytes of a [`CanFrameRef`], still written as text in the original line
+///
+/// The text has been validated while parsing, bytes are decoded on iteration.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct PayloadRef<'a> {
+    text: &'a str,
+    len: usize,
+    radix: u32,
+}
+
+impl<'a> PayloadRef<'a> {
+    fn empty(radix: u32) -> Self {
+        Self {
+            text: "",
+            len: 0,
+            radix,
+        }
+    }
+
+    /// The payload as written in the line, e.g. `cc 55 01 00`
+    pub fn as_str(&self) -> &'a str {
+        self.text.trim()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates over the decoded payload bytes
+    pub fn iter(&self) -> impl Iterator<Item = u8> + 'a {
+        let radix = self.radix;
+        self.text
+            .split_whitespace()
+            .filter_map(move |token| u8::from_str_radix(token, radix).ok())
+    }
+
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::io::BufRead;
+    use std::str::FromStr;
+
+    #[test]
+    fn parse_borrowed_frame() {
+        let line = "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872";
+        let frame = CanFrameRef::parse_line(line).unwrap();
+        assert_eq!(frame.bus_id, 3);
+        assert_eq!(frame.id, 0x368);
+        assert_eq!(frame.payload.len(), 4);
+        assert_eq!(frame.payload.as_str(), "cc 55 01 00");
+        assert_eq!(frame.payload.to_vec(), vec![0xCC, 0x55, 0x01, 0x00]);
+
+        let remote = CanFrameRef::parse_line("0.5 1 123 Rx r 8").unwrap();
+        assert!(remote.is_rtr);
+        assert!(remote.payload.is_empty());
+
+        assert_eq!(
+            CanFrameRef::parse_line("0.962604 3 368 Rx d 4 cc 55 01"),
+            Err(AscParseError::InvalidPayloadLength { exp: 4, act: 3 })
+        );
+        assert!(CanFrameRef::parse_line("0.962604 3 368 Rx d 2 cc zz").is_err());
+    }
+
+    #[test]
+    fn convert_to_owned_frame() {
+        let lines = [
+            "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872",
+            "0.962892 3 1f78c410x Rx d 8 02 00 00 00 24 00 70 03",
+            "0.5 1 123 Rx r 8",
+            "7.392600 CANFD 1 Rx 6e 1 0 6 6 00 04 00 00 ff 00 0 0 3000 0 0 0 0 0",
+        ];
+        for line in lines.iter() {
+            assert_eq!(
+                CanFrameRef::parse_line(line).unwrap().to_owned(),
+                CanFrame::from_str(line).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn reuse_line_buffer() {
+        let input = "0.1 1 100 Rx d 1 01\n0.2 1 101 Rx d 2 02 03\n";
+        let mut reader = input.as_bytes();
+        let mut buffer = String::new();
+        let mut frames = vec![];
+
+        while reader.read_line(&mut buffer).unwrap() > 0 {
+            let frame = CanFrameRef::parse_line(&buffer).unwrap();
+            frames.push(frame.to_owned());
+            buffer.clear();
+        }
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].id, 0x101);
+        assert_eq!(frames[1].payload, vec![0x02, 0x03]);
+    }
+}
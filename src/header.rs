@@ -0,0 +1,248 @@
+use std::str::FromStr;
+
+/// The numeric base ids and payload bytes are written in
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NumericBase {
+    #[default]
+    Hex,
+    Dec,
+}
+
+impl NumericBase {
+    pub fn radix(self) -> u32 {
+        match self {
+            NumericBase::Hex => 16,
+            NumericBase::Dec => 10,
+        }
+    }
+}
+
+impl FromStr for NumericBase {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(NumericBase::Hex),
+            "dec" => Ok(NumericBase::Dec),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Whether timestamps count from the measurement start or from the previous event
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimestampMode {
+    #[default]
+    Absolute,
+    Relative,
+}
+
+impl FromStr for TimestampMode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "absolute" => Ok(TimestampMode::Absolute),
+            "relative" => Ok(TimestampMode::Relative),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Format information declared in the leading lines of an ASC file
+///
+/// Files without a header are treated as `base hex timestamps absolute`.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AscHeader {
+    pub base: NumericBase,
+    pub timestamps: TimestampMode,
+    /// The measurement start as written in the `date` line, e.g. `Mon Sep 14 10:11:12.345 am 2020`
+    pub date: Option<String>,
+}
+
+impl AscHeader {
+    /// Updates the header from the given line, returning whether it was a header line
+    pub fn parse_line(&mut self, line: &str) -> bool {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("base") => {
+                if let Some(base) = tokens.next().and_then(|t| NumericBase::from_str(t).ok()) {
+                    self.base = base;
+                }
+                if let Some("timestamps") = tokens.next() {
+                    if let Some(mode) = tokens.next().and_then(|t| TimestampMode::from_str(t).ok())
+                    {
+                        self.timestamps = mode;
+                    }
+                }
+                true
+            }
+            Some("date") => {
+                self.date = Some(tokens.collect::<Vec<_>>().join(" "));
+                true
+            }
+            Some("internal") | Some("no") => line
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+                .ends_with("internal events logged"),
+            _ => false,
+        }
+    }
+
+    /// The time of day of the measurement start in seconds since midnight
+    ///
+    /// Unlike [`AscHeader::start_time`], this only needs the clock part of the `date` line, e.g.
+    /// `10:11:12.345 am`, and does not depend on chrono.
+    pub fn start_of_day(&self) -> Option<f64> {
+        let mut tokens = self.date.as_ref()?.split_whitespace();
+        let mut seconds = tokens
+            .find(|token| token.contains(':'))
+            .and_then(parse_clock)?;
+        match tokens.next() {
+            Some(meridiem) if meridiem.eq_ignore_ascii_case("pm") && seconds < 12.0 * 3600.0 => {
+                seconds += 12.0 * 3600.0
+            }
+            Some(meridiem) if meridiem.eq_ignore_ascii_case("am") && seconds >= 12.0 * 3600.0 => {
+                seconds -= 12.0 * 3600.0
+            }
+            _ => {}
+        }
+        Some(seconds)
+    }
+
+    /// The measurement start declared in the `date` line
+    ///
+    /// ASC files do not record a time zone, so the date is returned as local time of the
+    /// recording machine.
+    #[cfg(feature = "chrono")]
+    pub fn start_time(&self) -> Option<chrono::NaiveDateTime> {
+        const FORMATS: [&str; 4] = [
+            "%a %b %d %I:%M:%S%.f %p %Y",
+            "%a %b %d %I:%M:%S %p %Y",
+            "%a %b %d %H:%M:%S%.f %Y",
+            "%a %b %d %H:%M:%S %Y",
+        ];
+
+        let date = self.date.as_ref()?;
+        FORMATS
+            .iter()
+            .find_map(|format| chrono::NaiveDateTime::parse_from_str(date, format).ok())
+    }
+
+    /// Converts a timestamp in seconds since measurement start into wall-clock time
+    #[cfg(feature = "chrono")]
+    pub fn absolute_time(&self, timestamp: f64) -> Option<chrono::NaiveDateTime> {
+        let offset = chrono::Duration::microseconds((timestamp * 1e6).round() as i64);
+        self.start_time()
+            .and_then(|start| start.checked_add_signed(offset))
+    }
+}
+
+/// Parses a clock time like `10:11:12.345` into seconds since midnight
+pub(crate) fn parse_clock(s: &str) -> Option<f64> {
+    let mut parts = s.split(':');
+    let hours = u32::from_str(parts.next()?).ok()?;
+    let minutes = u32::from_str(parts.next()?).ok()?;
+    let seconds = f64::from_str(parts.next()?).ok()?;
+    if parts.next().is_some() || hours > 23 || minutes > 59 || !(0.0..60.0).contains(&seconds) {
+        return None;
+    }
+    Some((hours * 3600 + minutes * 60) as f64 + seconds)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_base_line() {
+        let mut header = AscHeader::default();
+        assert_eq!(header.base, NumericBase::Hex);
+
+        assert!(header.parse_line("base dec  timestamps absolute"));
+        assert_eq!(header.base, NumericBase::Dec);
+
+        assert!(header.parse_line("base hex  timestamps absolute"));
+        assert_eq!(header.base, NumericBase::Hex);
+
+        assert!(!header
+            .parse_line("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872"));
+    }
+
+    #[test]
+    fn parse_timestamp_mode() {
+        let mut header = AscHeader::default();
+        assert_eq!(header.timestamps, TimestampMode::Absolute);
+
+        assert!(header.parse_line("base hex  timestamps relative"));
+        assert_eq!(header.timestamps, TimestampMode::Relative);
+
+        assert!(header.parse_line("base hex  timestamps absolute"));
+        assert_eq!(header.timestamps, TimestampMode::Absolute);
+    }
+
+    #[test]
+    fn parse_internal_events_line() {
+        let mut header = AscHeader::default();
+        assert!(header.parse_line("internal events logged"));
+        assert!(header.parse_line("no internal events logged"));
+        assert!(header.parse_line("no\tinternal  events\tlogged\r"));
+        assert!(!header.parse_line("no frame"));
+    }
+
+    #[test]
+    fn parse_date_line() {
+        let mut header = AscHeader::default();
+        assert!(header.parse_line("date Mon Sep 14 10:11:12.345 am 2020"));
+        assert_eq!(
+            header.date,
+            Some(String::from("Mon Sep 14 10:11:12.345 am 2020"))
+        );
+    }
+
+    #[test]
+    fn parse_start_of_day() {
+        let mut header = AscHeader::default();
+        assert_eq!(header.start_of_day(), None);
+
+        header.parse_line("date Mon Sep 14 10:11:12.25 am 2020");
+        assert_eq!(header.start_of_day(), Some(36672.25));
+        header.parse_line("date Mon Sep 14 10:11:12.5 pm 2020");
+        assert_eq!(header.start_of_day(), Some(79872.5));
+        header.parse_line("date Mon Sep 14 12:00:00 am 2020");
+        assert_eq!(header.start_of_day(), Some(0.0));
+        header.parse_line("date Mon Sep 14 22:11:12 2020");
+        assert_eq!(header.start_of_day(), Some(79872.0));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn convert_to_absolute_time() {
+        use chrono::NaiveDate;
+
+        let mut header = AscHeader::default();
+        assert_eq!(header.absolute_time(1.0), None);
+
+        header.parse_line("date Mon Sep 14 10:11:12.345 pm 2020");
+        let start = NaiveDate::from_ymd_opt(2020, 9, 14)
+            .and_then(|date| date.and_hms_milli_opt(22, 11, 12, 345))
+            .unwrap();
+        assert_eq!(header.start_time(), Some(start));
+        assert_eq!(
+            header.absolute_time(0.962604),
+            NaiveDate::from_ymd_opt(2020, 9, 14)
+                .and_then(|date| date.and_hms_micro_opt(22, 11, 13, 307604))
+        );
+
+        header.parse_line("date Mon Sep 14 22:11:12 2020");
+        assert_eq!(
+            header.start_time(),
+            NaiveDate::from_ymd_opt(2020, 9, 14).and_then(|date| date.and_hms_opt(22, 11, 12))
+        );
+    }
+}
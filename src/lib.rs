@@ -1,18 +1,134 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::io::BufRead;
 use std::io::BufReader;
-use std::io::Lines;
 use std::io::Read;
+use std::io::{Seek, SeekFrom};
 use std::str::FromStr;
 
 use thiserror::Error;
 
-#[derive(Debug, PartialEq)]
+use header::parse_clock;
+
+mod analysis;
+mod builder;
+mod downsample;
+mod fd_flags;
+mod file;
+mod filter;
+mod follow;
+mod frame_ref;
+mod header;
+mod merge;
+mod parsed_line;
+mod pcap;
+mod realtime;
+mod replay;
+mod statistics;
+mod writer;
+
+#[cfg(feature = "tokio")]
+mod async_parser;
+#[cfg(feature = "dbc")]
+mod dbc;
+#[cfg(feature = "embedded-can")]
+mod embedded;
+#[cfg(feature = "heapless")]
+mod fixed;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+mod socket;
+
+#[cfg(all(feature = "socketcan", target_os = "linux"))]
+pub use socket::{FrameConversionError, SendError};
+
+#[cfg(feature = "tokio")]
+pub use async_parser::AsyncAscParser;
+#[cfg(feature = "dbc")]
+pub use can_dbc::Dbc;
+#[cfg(feature = "dbc")]
+pub use dbc::{read_dbc, DbcReadError};
+#[cfg(feature = "heapless")]
+pub use fixed::{FixedCanFrame, FixedPayload};
+#[cfg(feature = "rayon")]
+pub use parallel::par_parse;
+
+pub use analysis::{
+    bus_load, bus_load_per_bus, cycle_times, diff, find_gaps, group_by_id, snapshot_at, BusLoad,
+    Gap, IdDiff,
+};
+pub use builder::CanFrameBuilder;
+pub use downsample::DownsampleByTime;
+pub use fd_flags::FdFlags;
+pub use file::AscFile;
+pub use filter::{InvalidPayloadMatch, PayloadMatch};
+pub use follow::Follow;
+pub use frame_ref::{CanFrameRef, PayloadRef};
+pub use header::{AscHeader, NumericBase, TimestampMode};
+pub use merge::{merge, remap_bus, AscMerger, RemapBus};
+pub use parsed_line::ParsedLine;
+pub use pcap::PcapngWriter;
+pub use realtime::Realtime;
+pub use replay::{replay, FrameSender};
+pub use statistics::BusStatistics;
+pub use writer::AscWriter;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    Rx,
+    Tx,
+}
+
+impl FromStr for Direction {
+    type Err = AscParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Rx" => Ok(Direction::Rx),
+            "Tx" => Ok(Direction::Tx),
+            _ => Err(AscParseError::InvalidDirection { str: s.to_string() }),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Direction::Rx => write!(f, "Rx"),
+            Direction::Tx => write!(f, "Tx"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CanFrame {
-    pub timestamp: f32,
-    pub bus_id: u8,
+    pub timestamp: f64,
+    pub bus_id: u16,
     pub id: u32,
+    pub is_extended: bool,
+    pub direction: Direction,
+    pub is_rtr: bool,
+    /// Whether the frame is a CAN FD frame, i.e. it was parsed from a `CANFD` line
+    pub is_fd: bool,
+    pub brs: bool,
+    pub esi: bool,
+    pub dlc: u8,
     pub length: usize,
     pub payload: Vec<u8>,
+    /// The length of the frame on the wire in bits, from the trailing `Length = ` field of
+    /// classic lines
+    pub frame_length_bits: Option<u32>,
+    /// The number of bits including stuff bits, from the trailing `BitCount = ` field of classic
+    /// lines
+    pub bit_count: Option<u32>,
+    /// The `Flags` field of CANFD lines, `None` for classic lines and lines ending early
+    pub fd_flags: Option<FdFlags>,
 }
 
 impl CanFrame {
@@ -21,276 +137,3184 @@ impl CanFrame {
             timestamp: 0.0,
             bus_id: 0,
             id: 0,
+            is_extended: false,
+            direction: Direction::Rx,
+            is_rtr: false,
+            is_fd: false,
+            brs: false,
+            esi: false,
+            dlc: 0,
             length: 0,
             payload: vec![],
+            frame_length_bits: None,
+            bit_count: None,
+            fd_flags: None,
         }
     }
-}
 
-pub struct AscParser<R: Read> {
-    lines: Lines<BufReader<R>>,
-}
+    /// The time of day the frame was logged at in seconds since midnight
+    ///
+    /// This is the timestamp as written by loggers using clock times instead of seconds since
+    /// the measurement start. Without a `date` line in the header, the measurement is taken to
+    /// start at midnight.
+    pub fn time_of_day(&self, header: &AscHeader) -> f64 {
+        (header.start_of_day().unwrap_or(0.0) + self.timestamp) % SECONDS_PER_DAY
+    }
 
-impl<R> AscParser<R>
-where
-    R: Read,
-{
-    pub fn new(input: R) -> Self {
-        let reader = BufReader::new(input);
-        Self {
-            lines: reader.lines(),
+    /// The frame type marker as written in the line, e.g. to reproduce it when re-encoding
+    ///
+    /// Classic lines mark data frames with `d` and remote frames with `r`, CANFD lines carry the
+    /// bit rate switch and error state indicator flags as `0` or `1` tokens instead, e.g. `1 0`.
+    /// The parser accepts no other spelling of these markers, so they follow from the flags of
+    /// the frame. The extended id is marked by the `x` suffix of the id, see `is_extended`.
+    pub fn raw_flags(&self) -> &'static str {
+        match (self.is_fd, self.brs, self.esi) {
+            (true, false, false) => "0 0",
+            (true, false, true) => "0 1",
+            (true, true, false) => "1 0",
+            (true, true, true) => "1 1",
+            (false, _, _) if self.is_rtr => "r",
+            (false, _, _) => "d",
         }
     }
-}
 
-impl<R> Iterator for AscParser<R>
-where
-    R: Read,
-{
-    type Item = CanFrame;
+    /// Whether both frames carry the same message on the same bus, regardless of when
+    ///
+    /// Compares bus, id, length and payload but not the timestamp, which the derived
+    /// `PartialEq` includes and which rarely matches between two captures.
+    pub fn content_eq(&self, other: &CanFrame) -> bool {
+        self.bus_id == other.bus_id
+            && self.id == other.id
+            && self.is_extended == other.is_extended
+            && self.length == other.length
+            && self.payload == other.payload
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(Ok(line)) = self.lines.next() {
-            match CanFrame::from_str(&line) {
-                Ok(frame) => return Some(frame),
-                Err(_) => return self.next(),
+    /// Renders the frame as a line of a Vector ASC file
+    ///
+    /// CAN FD frames are written as `CANFD` lines, all others as classic lines.
+    /// Fields the parser does not retain, like the CRC, and missing bit counts are written as
+    /// zero.
+    pub fn to_asc_line(&self) -> String {
+        let id = match self.is_extended {
+            true => format!("{:x}x", self.id),
+            false => format!("{:x}", self.id),
+        };
+        let payload: String = self
+            .payload
+            .iter()
+            .map(|byte| format!(" {:02x}", byte))
+            .collect();
+
+        if self.is_fd {
+            // Other bits of the parsed flags are kept, the frame format bits follow the frame
+            let known = FdFlags::EDL | FdFlags::BRS | FdFlags::ESI;
+            let mut flags = FdFlags(self.fd_flags.map_or(0, FdFlags::bits) & !known.bits());
+            flags = flags | FdFlags::EDL;
+            if self.brs {
+                flags = flags | FdFlags::BRS;
+            }
+            if self.esi {
+                flags = flags | FdFlags::ESI;
             }
+            format!(
+                "{:.6} CANFD {} {} {} {} {} {:x} {}{} 0 0 {} 0 0 0 0 0",
+                self.timestamp,
+                self.bus_id,
+                self.direction,
+                id,
+                self.brs as u8,
+                self.esi as u8,
+                self.dlc,
+                self.length,
+                payload,
+                flags
+            )
+        } else {
+            let decimal_id = match self.is_extended {
+                true => format!("{}x", self.id),
+                false => format!("{}", self.id),
+            };
+            format!(
+                "{:.6} {} {} {} {} {}{} Length = {} BitCount = {} ID = {}",
+                self.timestamp,
+                self.bus_id,
+                id,
+                self.direction,
+                if self.is_rtr { "r" } else { "d" },
+                self.dlc,
+                payload,
+                self.frame_length_bits.unwrap_or(0),
+                self.bit_count.unwrap_or(0),
+                decimal_id
+            )
         }
-        None
     }
 }
 
-#[derive(Error, Debug, PartialEq)]
-pub enum AscParseError {
-    #[error("Cannot parse timestamp {str:?}")]
-    InvalidTimestamp { str: String },
+/// Frames compare equal only if all fields are equal, so parsed timestamps are expected not to be
+/// NaN
+impl Eq for CanFrame {}
 
-    #[error("Cannot parse bus id {str:?}")]
-    InvalidBusId { str: String },
+/// Hashes the identity of a frame, its bus, id and payload, leaving out the timestamp
+impl Hash for CanFrame {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.bus_id.hash(state);
+        self.id.hash(state);
+        self.is_extended.hash(state);
+        self.payload.hash(state);
+    }
+}
 
-    #[error("Cannot parse frame id {str:?}")]
-    InvalidFrameId { str: String },
+/// Orders frames by timestamp, then bus id and then id, e.g. to sort frames collected from several
+/// logs
+///
+/// Timestamps are compared with [`f64::total_cmp`]. The remaining fields only break ties, so that
+/// frames compare equal just if they are equal. The exception are timestamps `-0.0` and `0.0`,
+/// which are equal but ordered, and NaN timestamps, which are never equal but ordered.
+impl Ord for CanFrame {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.timestamp
+            .total_cmp(&other.timestamp)
+            .then(self.bus_id.cmp(&other.bus_id))
+            .then(self.id.cmp(&other.id))
+            .then(self.is_extended.cmp(&other.is_extended))
+            .then(self.direction.cmp(&other.direction))
+            .then(self.is_rtr.cmp(&other.is_rtr))
+            .then(self.is_fd.cmp(&other.is_fd))
+            .then(self.brs.cmp(&other.brs))
+            .then(self.esi.cmp(&other.esi))
+            .then(self.dlc.cmp(&other.dlc))
+            .then(self.length.cmp(&other.length))
+            .then_with(|| self.payload.cmp(&other.payload))
+            .then(self.frame_length_bits.cmp(&other.frame_length_bits))
+            .then(self.bit_count.cmp(&other.bit_count))
+            .then(self.fd_flags.cmp(&other.fd_flags))
+    }
+}
 
-    #[error("Cannot parse length field {str:?}")]
-    InvalidLengthField { str: String },
+impl PartialOrd for CanFrame {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
 
-    #[error("Cannot parse length field {str:?}")]
-    InvalidPayload { str: String },
+/// Renders the frame in the style of the Linux `candump` tool, e.g. `can3 368 [4] CC 55 01 00`
+impl fmt::Display for CanFrame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "can{} ", self.bus_id)?;
+        match self.is_extended {
+            true => write!(f, "{:08X}", self.id)?,
+            false => write!(f, "{:03X}", self.id)?,
+        }
+        write!(f, " [{}]", self.length)?;
+        if self.is_rtr {
+            return write!(f, "  remote request");
+        }
+        for byte in &self.payload {
+            write!(f, " {:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
 
-    #[error("Inconsistent payload length: {exp:?} != {act:?}")]
-    InvalidPayloadLength { exp: usize, act: usize },
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorFrame {
+    pub timestamp: f64,
+    pub bus_id: u16,
+}
 
-    #[error("Invalid format: '{str:?}'")]
-    InvalidFormat { str: String },
+/// The kind of a non-frame event line, see [`Event`]
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EventKind {
+    /// `Start of measurement`
+    StartOfMeasurement,
+    /// Trigger markers like `log trigger event`
+    Trigger,
+    /// Overload frames reported by the controller
+    Overload,
+    /// Controller status changes like `CAN 1 Status:chip status error active`
+    Status,
 }
 
-impl FromStr for CanFrame {
-    type Err = AscParseError;
+/// A timestamped line marking an event instead of a frame
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event {
+    pub timestamp: f64,
+    pub kind: EventKind,
+    /// The line following the timestamp, e.g. `Start of measurement`
+    pub text: String,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut frame = Self::new();
-        let mut tokens = s.split_whitespace();
-        let can_fd = s.contains("CANFD");
+impl Event {
+    /// Parses an event line, returning `None` for lines not recognized as event
+    pub fn parse_line(line: &str) -> Option<Self> {
+        Self::parse_with_header(line, &AscHeader::default())
+    }
 
-        if let Some(timestamp_token) = tokens.next() {
-            frame.timestamp =
-                f32::from_str(timestamp_token).map_err(|err| AscParseError::InvalidTimestamp {
-                    str: err.to_string(),
-                })?;
-        } else {
-            return Err(AscParseError::InvalidFormat { str: s.to_string() });
-        }
+    /// Parses an event line using the timestamp format declared in the given header
+    fn parse_with_header(line: &str, header: &AscHeader) -> Option<Self> {
+        let line = line.trim_end_matches(['\n', '\r']);
+        let timestamp_token = line.split_whitespace().next()?;
+        let timestamp = parse_timestamp(timestamp_token, header).ok()?;
+        let text = remainder_after(line, timestamp_token).trim();
 
-        if let Some(bus_id_token) = match can_fd {
-            true => tokens.nth(1),
-            false => tokens.next(),
-        } {
-            frame.bus_id =
-                u8::from_str(bus_id_token).map_err(|err| AscParseError::InvalidBusId {
-                    str: err.to_string(),
-                })?;
+        let mut tokens = text.split_whitespace();
+        let kind = if text.starts_with("Start of measurement") {
+            EventKind::StartOfMeasurement
+        } else if tokens
+            .clone()
+            .any(|token| token.eq_ignore_ascii_case("trigger"))
+        {
+            EventKind::Trigger
+        } else if tokens
+            .clone()
+            .any(|token| token.starts_with("OverloadFrame"))
+        {
+            EventKind::Overload
+        } else if tokens.any(|token| token.starts_with("Status:")) {
+            EventKind::Status
         } else {
-            return Err(AscParseError::InvalidFormat { str: s.to_string() });
-        }
+            return None;
+        };
 
-        if let Some(id_token) = match can_fd {
-            true => tokens.nth(1),
-            false => tokens.next(),
-        } {
-            frame.id = u32::from_str_radix(id_token.trim_end_matches('x'), 16).map_err(|err| {
-                AscParseError::InvalidFrameId {
-                    str: err.to_string(),
-                }
-            })?;
-        } else {
-            return Err(AscParseError::InvalidFormat { str: s.to_string() });
-        }
+        Some(Self {
+            timestamp,
+            kind,
+            text: text.to_string(),
+        })
+    }
+}
 
-        if let Some(length_token) = match can_fd {
-            true => tokens.nth(3),
-            false => tokens.nth(2),
-        } {
-            frame.length =
-                usize::from_str(length_token).map_err(|err| AscParseError::InvalidLengthField {
-                    str: err.to_string(),
-                })?;
-            frame.payload = tokens
-                .take(frame.length)
-                .map(|t| u8::from_str_radix(t, 16))
-                .collect::<Result<Vec<u8>, _>>()
-                .map_err(|err| AscParseError::InvalidPayload {
-                    str: err.to_string(),
-                })?;
-        } else {
-            return Err(AscParseError::InvalidFormat { str: s.to_string() });
-        }
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AscEntry {
+    Data(CanFrame),
+    Error(ErrorFrame),
+    /// Only yielded if enabled with [`AscParserOptions::yield_events`]
+    Event(Event),
+}
 
-        if frame.payload.len() != frame.length {
-            return Err(AscParseError::InvalidPayloadLength {
-                exp: frame.length,
-                act: frame.payload.len(),
-            });
-        }
+/// Iterates over the frames of a Vector ASC log, skipping lines that are not frames
+///
+/// Fields may be separated by any mix of spaces and tabs.
+pub struct AscParser<R: Read> {
+    reader: Input<R>,
+    buffer: String,
+    line: usize,
+    header: AscHeader,
+    elapsed: f64,
+    block_started: bool,
+    block_offset: f64,
+    last_timestamp: f64,
+    peeked: Option<CanFrame>,
+    stopped: bool,
+    options: AscParserOptions,
+    statistics: Vec<BusStatistics>,
+}
 
-        Ok(frame)
+impl<R> AscParser<R>
+where
+    R: Read,
+{
+    pub fn new(input: R) -> Self {
+        Self::with_options(input, AscParserOptions::default())
     }
-}
 
-#[cfg(test)]
-mod tests {
+    /// Creates a parser with the given options, see [`AscParserOptions`]
+    pub fn with_options(input: R, options: AscParserOptions) -> Self {
+        Self::with_input(Input::Unbuffered(BufReader::new(input)), options)
+    }
 
-    use super::*;
+    fn with_input(reader: Input<R>, options: AscParserOptions) -> Self {
+        let mut header = AscHeader::default();
+        if let Some(base) = options.base {
+            header.base = base;
+        }
+        Self {
+            reader,
+            buffer: String::new(),
+            line: 0,
+            header,
+            elapsed: 0.0,
+            block_started: false,
+            block_offset: 0.0,
+            last_timestamp: 0.0,
+            peeked: None,
+            stopped: false,
+            options,
+            statistics: vec![],
+        }
+    }
 
-    #[test]
-    fn parse_can_frame_from_string_timestamp() {
-        let line =
-            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
-        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
-        assert_eq!(0.962604, frame.timestamp);
+    /// Creates a parser that optionally re-bases timestamps at `Begin Triggerblock` markers
+    ///
+    /// Some loggers restart the clock for every trigger block. With `rebase` set, a block whose
+    /// timestamps start before the end of the previous block is shifted to continue from there,
+    /// so timestamps keep increasing across the whole file.
+    pub fn with_trigger_block_rebase(input: R, rebase: bool) -> Self {
+        Self::with_options(
+            input,
+            AscParserOptions::default().rebase_trigger_blocks(rebase),
+        )
+    }
 
-        let invalid =
-            String::from("0.9xxxxx 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
-        assert_eq!(true, CanFrame::from_str(&invalid).is_err());
+    /// Sets whether frames with fewer payload bytes than their length states are accepted
+    ///
+    /// Some loggers truncate the payload when they had partial data only. With `allow` set,
+    /// such payloads are padded with zeros up to the stated length instead of failing with
+    /// [`AscParseError::InvalidPayloadLength`]. The payload ends at the first token that is
+    /// not a byte.
+    pub fn allow_short_payload(mut self, allow: bool) -> Self {
+        self.options.allow_short_payload = allow;
+        self
+    }
 
-        let invalid_length = String::from("");
-        assert_eq!(true, CanFrame::from_str(&invalid_length).is_err());
+    /// The header information encountered so far
+    ///
+    /// Header lines are consumed while iterating, so the header is complete once the first
+    /// frame has been yielded.
+    pub fn header(&self) -> &AscHeader {
+        &self.header
     }
 
-    #[test]
-    fn parse_can_frame_from_string_bus_id() {
-        let line =
-            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
-        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
-        assert_eq!(frame.bus_id, 3);
+    /// The `Statistic:` lines encountered so far, in the order of the input
+    ///
+    /// Loggers usually write them at the end of the measurement, so they are complete once
+    /// iterating has finished.
+    pub fn statistics(&self) -> &[BusStatistics] {
+        &self.statistics
+    }
 
-        let invalid =
-            String::from("0.962604 _ 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
-        assert_eq!(true, CanFrame::from_str(&invalid).is_err());
+    /// Converts the timestamp of a frame into wall-clock time using the header `date` line
+    #[cfg(feature = "chrono")]
+    pub fn absolute_time(&self, frame: &CanFrame) -> Option<chrono::NaiveDateTime> {
+        self.header.absolute_time(frame.timestamp)
+    }
 
-        let invalid_length = String::from("0.962604");
-        assert_eq!(true, CanFrame::from_str(&invalid_length).is_err());
+    /// Returns the next frame without consuming it
+    ///
+    /// Lines failing to parse are skipped just like during regular iteration.
+    pub fn peek(&mut self) -> Option<&CanFrame> {
+        if self.peeked.is_none() {
+            self.peeked = self.next();
+        }
+        self.peeked.as_ref()
+    }
 
-        let line_canfd =
-            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
-        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
-        assert_eq!(frame.bus_id, 1);
+    /// Turns the parser into an iterator over all recognized entries, including error frames
+    pub fn entries(self) -> AscEntries<R> {
+        AscEntries { parser: self }
     }
 
-    #[test]
-    fn parse_can_frame_from_string_can_id() {
-        let line =
-            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
-        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
-        assert_eq!(frame.id, 0x368);
+    /// Turns the parser into an iterator that reports lines failing to parse instead of skipping
+    /// them
+    ///
+    /// The skipping behavior of the parser itself can be reproduced by discarding the errors:
+    ///
+    /// ```
+    /// use ascdump::AscParser;
+    ///
+    /// let input = "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+    ///              this is not a frame";
+    /// let frames: Vec<_> = AscParser::new(input.as_bytes())
+    ///     .try_frames()
+    ///     .filter_map(Result::ok)
+    ///     .collect();
+    /// assert_eq!(frames.len(), 1);
+    /// ```
+    pub fn try_frames(self) -> TryFrames<R> {
+        TryFrames { parser: self }
+    }
 
-        let invalid =
-            String::from("0.962604 3 3_8 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
-        assert_eq!(true, CanFrame::from_str(&invalid).is_err());
+    /// Turns the parser into an iterator over frames paired with the line they were parsed from
+    ///
+    /// The line is returned without its line terminator.
+    pub fn with_raw_lines(self) -> WithRawLines<R> {
+        WithRawLines { parser: self }
+    }
 
-        let invalid_length = String::from("0.962604 3");
-        assert_eq!(true, CanFrame::from_str(&invalid_length).is_err());
+    /// Turns the parser into an iterator over frames with timestamps relative to the first frame
+    ///
+    /// The first frame yielded has the timestamp 0.0, which eases comparing logs recorded with
+    /// different offsets.
+    pub fn rebased(self) -> Rebased<R> {
+        Rebased {
+            parser: self,
+            start: None,
+        }
+    }
 
-        let line_canfd =
-            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
-        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
-        assert_eq!(frame.id, 0x6e);
+    /// Turns the parser into an iterator skipping frames that repeat the frame right before them
+    ///
+    /// Frames are considered repeated if they match in content, see [`CanFrame::content_eq`]. Such duplicates usually stem from overlapping captures.
+    pub fn dedup_consecutive(self) -> DedupConsecutive<R> {
+        DedupConsecutive {
+            parser: self,
+            last: None,
+        }
     }
 
-    #[test]
-    fn parse_can_frame_from_string_extended_can_id() {
-        let line =
-            String::from("0.962892 3 1f78c410x Rx d 8 02 00 00 00 24 00 70 03 Length = 0 BitCount = 0 ID = 528008208x");
-        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
-        assert_eq!(frame.id, 0x1f78c410);
+    /// Turns the parser into an iterator yielding frames at the pace they were logged at
+    ///
+    /// A speed of 2.0 replays twice as fast, see [`Realtime`] for details.
+    ///
+    /// # Panics
+    ///
+    /// If the speed is not positive.
+    pub fn realtime(self, speed: f64) -> Realtime<Self> {
+        Realtime::new(self, speed)
+    }
 
-        let line_canfd = String::from(
-            "7.392600 CANFD 1 Rx 12b80210x 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0",
-        );
-        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
-        assert_eq!(frame.id, 0x12b80210);
+    /// Turns the parser into an iterator yielding at most one frame per id and interval
+    ///
+    /// Frames are yielded if their id has not been seen for at least the interval in seconds,
+    /// which reduces the volume of large logs before plotting them. Ids are told apart like in
+    /// [`group_by_id`].
+    pub fn downsample_by_time(self, interval: f64) -> DownsampleByTime<Self> {
+        DownsampleByTime::new(self, interval)
     }
 
-    #[test]
-    fn parse_can_frame_from_string_payload() {
-        let line =
-            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
-        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
-        assert_eq!(frame.length, 4);
-        assert_eq!(frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+    /// Turns the parser into an iterator yielding the first and then every nth frame
+    ///
+    /// # Panics
+    ///
+    /// If n is zero.
+    pub fn downsample_by_nth(self, n: usize) -> std::iter::StepBy<Self> {
+        self.step_by(n)
+    }
 
-        let invalid_length_field =
-            String::from("0.962604 3 368 Rx d _ cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
-        assert_eq!(true, CanFrame::from_str(&invalid_length_field).is_err());
+    /// Reads the next line that is neither blank, a comment, part of the header, a trigger block
+    /// marker nor a `Statistic:` line into the line buffer, updating the parser state on the way
+    ///
+    /// The buffer is reused for every line, so reading does not allocate once it has grown to
+    /// the longest line. Returns `false` at the end of the input, on a read error or once a
+    /// strict parser encountered a line failing to parse.
+    fn next_line(&mut self) -> bool {
+        if self.stopped {
+            return false;
+        }
+        loop {
+            self.buffer.clear();
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) | Err(_) => return false,
+                Ok(_) => {}
+            }
+            if self.accept_buffer() {
+                return true;
+            }
+        }
+    }
 
-        let invalid_payload =
-            String::from("0.962604 3 368 Rx d 4 cc 55 __ 00 Length = 0 BitCount = 0 ID = 872");
-        assert_eq!(true, CanFrame::from_str(&invalid_payload).is_err());
+    /// Takes in the line just read into the buffer, returning whether it may hold a frame
+    ///
+    /// The line terminator is removed from the buffer. Lines that do not hold frames update the
+    /// parser state instead, e.g. header lines.
+    fn accept_buffer(&mut self) -> bool {
+        if self.buffer.ends_with('\n') {
+            self.buffer.pop();
+            if self.buffer.ends_with('\r') {
+                self.buffer.pop();
+            }
+        }
 
-        let invalid_length_1 = String::from("0.962604 3 368 Rx d");
-        assert_eq!(true, CanFrame::from_str(&invalid_length_1).is_err());
+        self.line += 1;
+        // Blank lines are no errors, not even for a strict parser
+        if self.buffer.trim().is_empty() || self.options.is_comment(&self.buffer) {
+            return false;
+        }
+        if self.header.parse_line(&self.buffer) {
+            if let Some(base) = self.options.base {
+                self.header.base = base;
+            }
+            return false;
+        }
 
-        let invalid_length_2 = String::from("0.962604 3 368 Rx d 4 cc");
-        assert_eq!(true, CanFrame::from_str(&invalid_length_2).is_err());
+        let mut tokens = self.buffer.split_whitespace();
+        match (tokens.next(), tokens.next()) {
+            (Some("Begin"), Some(block)) if block.eq_ignore_ascii_case("triggerblock") => {
+                self.block_started = true;
+                false
+            }
+            (Some("End"), Some(block)) if block.eq_ignore_ascii_case("triggerblock") => false,
+            _ => match BusStatistics::parse_line(&self.buffer) {
+                Some(mut statistics) => {
+                    statistics.timestamp = statistics.timestamp.map(|t| self.since_start(t));
+                    self.statistics.push(statistics);
+                    false
+                }
+                None => true,
+            },
+        }
+    }
 
-        let line_canfd =
-            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
-        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
-        assert_eq!(frame.length, 6);
-        assert_eq!(frame.payload, vec![0xEC, 0x0A, 0x22, 0xFF, 0xFF, 0xF1]);
+    /// Parses the line held in the buffer as a frame
+    ///
+    /// A strict parser stops reading further lines once parsing failed on a line that is not an
+    /// event, error frame or statistics line either.
+    fn parse_buffer(&mut self) -> Result<CanFrame, AscParseError> {
+        let result = CanFrame::parse_with_options(&self.buffer, &self.header, &self.options);
+        self.stopped = result.is_err() && self.options.strict && !self.holds_event();
+        result
     }
 
-    #[test]
-    fn iterate_over_lines() {
-        let lines = String::from(
-            "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
-            7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0",
-        );
+    /// Whether the line held in the buffer is an event, error frame or statistics line
+    ///
+    /// Such lines hold no frame, but are no parse errors either.
+    fn holds_event(&self) -> bool {
+        event_timestamp(&self.buffer, &self.header).is_some()
+            || BusStatistics::parse_line(&self.buffer).is_some()
+    }
 
-        let mut parser = AscParser::new(lines.as_bytes());
+    /// Skips the event or error frame line held in the buffer, returning whether it was one
+    ///
+    /// The timestamp of the line still counts, as relative timestamps are deltas to the previous
+    /// line.
+    fn skip_event(&mut self) -> bool {
+        match event_timestamp(&self.buffer, &self.header) {
+            Some(timestamp) => {
+                self.since_start(timestamp);
+                true
+            }
+            None => self.holds_event(),
+        }
+    }
 
-        assert_eq!(
-            parser.next(),
-            Some(CanFrame {
-                timestamp: 0.962604,
-                bus_id: 3,
-                id: 0x368,
-                length: 4,
-                payload: vec![0xCC, 0x55, 0x01, 0x00]
-            })
-        );
-        assert_eq!(
+    /// Turns a timestamp into seconds since measurement start, accumulating relative timestamps
+    /// and re-basing trigger blocks if requested
+    fn since_start(&mut self, timestamp: f64) -> f64 {
+        let mut timestamp = match self.header.timestamps {
+            TimestampMode::Relative => {
+                self.elapsed += timestamp;
+                self.elapsed
+            }
+            TimestampMode::Absolute => timestamp,
+        };
+
+        if self.options.rebase_trigger_blocks {
+            if self.block_started && timestamp + self.block_offset < self.last_timestamp {
+                self.block_offset = self.last_timestamp;
+            }
+            self.block_started = false;
+            timestamp += self.block_offset;
+        }
+
+        self.last_timestamp = timestamp;
+        timestamp
+    }
+}
+
+impl<R> AscParser<R>
+where
+    R: BufRead,
+{
+    /// Creates a parser reading lines from an input that is buffered already
+    ///
+    /// Unlike [`AscParser::new`], the parser reads straight from the buffer of the input, e.g.
+    /// of a `BufReader<File>` or a locked stdin, instead of copying it into a buffer of its own.
+    pub fn from_bufread(reader: R) -> Self {
+        let input = Input::Buffered {
+            reader,
+            read_line: R::read_line,
+        };
+        Self::with_input(input, AscParserOptions::default())
+    }
+}
+
+impl<R> AscParser<R>
+where
+    R: Read + Seek,
+{
+    /// Positions the parser in front of the first frame at or after the given timestamp
+    ///
+    /// The input is binary searched, so it needs to be sorted by time and use absolute
+    /// timestamps. Header lines at the start of the input are read before searching. Line
+    /// numbers reported after seeking count from the new position.
+    pub fn seek_to_timestamp(&mut self, timestamp: f64) -> std::io::Result<()> {
+        if self.line == 0 {
+            self.peek();
+        }
+        self.peeked = None;
+
+        let mut low = 0;
+        let mut high = self.reader.seek(SeekFrom::End(0))?;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            match self.frame_from(mid)? {
+                Some((start, frame_timestamp)) if frame_timestamp < timestamp => low = start + 1,
+                _ => high = mid,
+            }
+        }
+
+        let start = match self.frame_from(low)? {
+            Some((start, _)) => start,
+            None => self.reader.seek(SeekFrom::End(0))?,
+        };
+        self.reader.seek(SeekFrom::Start(start))?;
+        self.line = 0;
+        Ok(())
+    }
+
+    /// Finds the first frame on a line starting at or after the given position, returning the
+    /// position of the line and the timestamp of the frame
+    fn frame_from(&mut self, position: u64) -> std::io::Result<Option<(u64, f64)>> {
+        let mut start = position;
+        if position > 0 {
+            // Re-align to the start of a line by skipping the rest of the preceding one
+            self.reader.seek(SeekFrom::Start(position - 1))?;
+            self.buffer.clear();
+            start = position - 1 + self.reader.read_line(&mut self.buffer)? as u64;
+        } else {
+            self.reader.seek(SeekFrom::Start(0))?;
+        }
+
+        loop {
+            self.buffer.clear();
+            let read = self.reader.read_line(&mut self.buffer)?;
+            if read == 0 {
+                return Ok(None);
+            }
+            let parsed = CanFrame::parse_with_options(&self.buffer, &self.header, &self.options);
+            if let Ok(frame) = parsed {
+                return Ok(Some((start, frame.timestamp)));
+            }
+            start += read as u64;
+        }
+    }
+}
+
+/// The input of a parser, read through a buffer of the parser unless it is buffered already
+enum Input<R: Read> {
+    Unbuffered(BufReader<R>),
+    Buffered {
+        reader: R,
+        /// `BufRead::read_line` of the input, which is only known to be `BufRead` on creation
+        read_line: fn(&mut R, &mut String) -> std::io::Result<usize>,
+    },
+}
+
+impl<R> Input<R>
+where
+    R: Read,
+{
+    fn read_line(&mut self, buffer: &mut String) -> std::io::Result<usize> {
+        match self {
+            Input::Unbuffered(reader) => reader.read_line(buffer),
+            Input::Buffered { reader, read_line } => read_line(reader, buffer),
+        }
+    }
+}
+
+impl<R> Input<R>
+where
+    R: Read + Seek,
+{
+    fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Input::Unbuffered(reader) => reader.seek(position),
+            Input::Buffered { reader, .. } => reader.seek(position),
+        }
+    }
+}
+
+impl<R> Iterator for AscParser<R>
+where
+    R: Read,
+{
+    type Item = CanFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(frame) = self.peeked.take() {
+            return Some(frame);
+        }
+
+        while self.next_line() {
+            match self.parse_buffer() {
+                Ok(mut frame) => {
+                    frame.timestamp = self.since_start(frame.timestamp);
+                    return Some(frame);
+                }
+                Err(_) => {
+                    self.skip_event();
+                }
+            }
+        }
+        None
+    }
+}
+
+pub struct AscEntries<R: Read> {
+    parser: AscParser<R>,
+}
+
+impl<R> AscEntries<R>
+where
+    R: Read,
+{
+    /// The header information encountered so far
+    pub fn header(&self) -> &AscHeader {
+        self.parser.header()
+    }
+}
+
+impl<R> Iterator for AscEntries<R>
+where
+    R: Read,
+{
+    type Item = AscEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(frame) = self.parser.peeked.take() {
+            return Some(AscEntry::Data(frame));
+        }
+
+        while self.parser.next_line() {
+            let entry = AscEntry::parse_with_options(
+                &self.parser.buffer,
+                &self.parser.header,
+                &self.parser.options,
+            );
+            self.parser.stopped =
+                entry.is_err() && self.parser.options.strict && !self.parser.holds_event();
+            match entry {
+                Ok(AscEntry::Data(mut frame)) => {
+                    frame.timestamp = self.parser.since_start(frame.timestamp);
+                    return Some(AscEntry::Data(frame));
+                }
+                Ok(AscEntry::Error(mut error)) => {
+                    error.timestamp = self.parser.since_start(error.timestamp);
+                    return Some(AscEntry::Error(error));
+                }
+                Ok(AscEntry::Event(mut event)) => {
+                    event.timestamp = self.parser.since_start(event.timestamp);
+                    return Some(AscEntry::Event(event));
+                }
+                Err(_) => {
+                    self.parser.skip_event();
+                }
+            }
+        }
+        None
+    }
+}
+
+pub struct WithRawLines<R: Read> {
+    parser: AscParser<R>,
+}
+
+impl<R> WithRawLines<R>
+where
+    R: Read,
+{
+    /// The header information encountered so far
+    pub fn header(&self) -> &AscHeader {
+        self.parser.header()
+    }
+}
+
+impl<R> Iterator for WithRawLines<R>
+where
+    R: Read,
+{
+    type Item = (CanFrame, String);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // A peeked frame was parsed from the line still held in the buffer
+        if let Some(frame) = self.parser.peeked.take() {
+            return Some((frame, self.parser.buffer.clone()));
+        }
+
+        while self.parser.next_line() {
+            match self.parser.parse_buffer() {
+                Ok(mut frame) => {
+                    frame.timestamp = self.parser.since_start(frame.timestamp);
+                    return Some((frame, self.parser.buffer.clone()));
+                }
+                Err(_) => {
+                    self.parser.skip_event();
+                }
+            }
+        }
+        None
+    }
+}
+
+pub struct Rebased<R: Read> {
+    parser: AscParser<R>,
+    start: Option<f64>,
+}
+
+impl<R> Rebased<R>
+where
+    R: Read,
+{
+    /// The header information encountered so far
+    pub fn header(&self) -> &AscHeader {
+        self.parser.header()
+    }
+}
+
+impl<R> Iterator for Rebased<R>
+where
+    R: Read,
+{
+    type Item = CanFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut frame = self.parser.next()?;
+        let start = *self.start.get_or_insert(frame.timestamp);
+        frame.timestamp -= start;
+        Some(frame)
+    }
+}
+
+pub struct DedupConsecutive<R: Read> {
+    parser: AscParser<R>,
+    last: Option<CanFrame>,
+}
+
+impl<R> DedupConsecutive<R>
+where
+    R: Read,
+{
+    /// The header information encountered so far
+    pub fn header(&self) -> &AscHeader {
+        self.parser.header()
+    }
+}
+
+impl<R> Iterator for DedupConsecutive<R>
+where
+    R: Read,
+{
+    type Item = CanFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let frame = self.parser.next()?;
+            let is_repetition = match &self.last {
+                Some(last) => frame.content_eq(last),
+                None => false,
+            };
+            if !is_repetition {
+                self.last = Some(frame.clone());
+                return Some(frame);
+            }
+        }
+    }
+}
+
+pub struct TryFrames<R: Read> {
+    parser: AscParser<R>,
+}
+
+impl<R> TryFrames<R>
+where
+    R: Read,
+{
+    /// The header information encountered so far
+    pub fn header(&self) -> &AscHeader {
+        self.parser.header()
+    }
+
+    /// The `Statistic:` lines encountered so far, see [`AscParser::statistics`]
+    pub fn statistics(&self) -> &[BusStatistics] {
+        self.parser.statistics()
+    }
+}
+
+impl<R> Iterator for TryFrames<R>
+where
+    R: Read,
+{
+    type Item = Result<CanFrame, LocatedError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(frame) = self.parser.peeked.take() {
+            return Some(Ok(frame));
+        }
+
+        while self.parser.next_line() {
+            match self.parser.parse_buffer() {
+                Ok(mut frame) => {
+                    frame.timestamp = self.parser.since_start(frame.timestamp);
+                    return Some(Ok(frame));
+                }
+                // Event and error frame lines are no frames, but no errors either
+                Err(_) if self.parser.skip_event() => {}
+                Err(source) => {
+                    return Some(Err(LocatedError {
+                        line: self.parser.line,
+                        source,
+                    }))
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Iterates over the frames of the input, handing every line that fails to parse to `on_error`
+/// together with its 1-based line number
+///
+/// The frames yielded are the same as for iterating an [`AscParser`] directly.
+pub fn parse_with_errors<R, F>(reader: R, mut on_error: F) -> impl Iterator<Item = CanFrame>
+where
+    R: Read,
+    F: FnMut(usize, AscParseError),
+{
+    AscParser::new(reader)
+        .try_frames()
+        .filter_map(move |result| match result {
+            Ok(frame) => Some(frame),
+            Err(error) => {
+                on_error(error.line, error.source);
+                None
+            }
+        })
+}
+
+/// Counts the frames of the input without decoding their payloads
+///
+/// Lines are accepted as frames if their leading fields up to the length parse, which is
+/// considerably faster than parsing them completely for large logs. Lines with a broken
+/// payload are therefore counted here, while iterating an [`AscParser`] skips them.
+pub fn count_frames<R: Read>(reader: R) -> usize {
+    let mut parser = AscParser::new(reader);
+    let mut count = 0;
+    while parser.next_line() {
+        if CanFrame::parse_fields(&parser.buffer, &parser.header).is_ok() {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// A parse error together with the 1-based number of the line it occurred on
+#[derive(Error, Debug, PartialEq)]
+#[error("line {line}: {source}")]
+pub struct LocatedError {
+    pub line: usize,
+    #[source]
+    pub source: AscParseError,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum AscParseError {
+    #[error("Cannot parse timestamp {str:?}")]
+    InvalidTimestamp { str: String },
+
+    #[error("Cannot parse bus id {str:?}")]
+    InvalidBusId { str: String },
+
+    #[error("Cannot parse frame id {str:?}")]
+    InvalidFrameId { str: String },
+
+    #[error("Cannot parse direction {str:?}")]
+    InvalidDirection { str: String },
+
+    #[error("Cannot parse flag {str:?}")]
+    InvalidFlag { str: String },
+
+    #[error("Cannot parse data length code {str:?}")]
+    InvalidDlc { str: String },
+
+    #[error("Cannot parse length field {str:?}")]
+    InvalidLengthField { str: String },
+
+    #[error("Cannot parse payload {str:?}")]
+    InvalidPayload { str: String },
+
+    #[error("Inconsistent payload length: {exp:?} != {act:?}")]
+    InvalidPayloadLength { exp: usize, act: usize },
+
+    #[error("Invalid format: '{str:?}'")]
+    InvalidFormat { str: String },
+
+    #[error("Invalid encoding: {str}")]
+    InvalidEncoding { str: String },
+
+    #[error("Empty line")]
+    EmptyLine,
+}
+
+/// Parses a single line of an ASC file without a header, i.e. with hex ids and payload
+///
+/// Use [`CanFrame::parse_with_header`] for lines of files declaring a different base, or an
+/// [`AscParser`] to read whole files.
+pub fn parse_line(line: &str) -> Result<CanFrame, AscParseError> {
+    CanFrame::parse_with_header(line, &AscHeader::default())
+}
+
+impl FromStr for CanFrame {
+    type Err = AscParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_line(s)
+    }
+}
+
+/// Parses a line given as raw bytes, e.g. from a memory mapped file
+///
+/// ASC files are plain ASCII, lines that are not valid UTF-8 fail with
+/// [`AscParseError::InvalidEncoding`].
+impl TryFrom<&[u8]> for CanFrame {
+    type Error = AscParseError;
+
+    fn try_from(line: &[u8]) -> Result<Self, Self::Error> {
+        let line = std::str::from_utf8(line).map_err(|err| AscParseError::InvalidEncoding {
+            str: err.to_string(),
+        })?;
+        parse_line(line)
+    }
+}
+
+/// The frames and the line numbers and errors of the lines failing to parse
+pub type PartialFrames = (Vec<CanFrame>, Vec<(usize, AscParseError)>);
+
+impl CanFrame {
+    /// Reads all frames of the input, failing if any line could not be parsed
+    ///
+    /// On failure, the frames parsed anyway are returned along with the errors. Keep in mind
+    /// that lines other than frames, `//` comments and recognized [`Event`] lines count as
+    /// errors as well.
+    pub fn collect_all<R: Read>(reader: R) -> Result<Vec<CanFrame>, PartialFrames> {
+        let mut errors = vec![];
+        let frames: Vec<CanFrame> =
+            parse_with_errors(reader, |line, error| errors.push((line, error))).collect();
+        match errors.is_empty() {
+            true => Ok(frames),
+            false => Err((frames, errors)),
+        }
+    }
+}
+
+/// Options changing how an [`AscParser`] reads its input, see [`AscParser::with_options`]
+///
+/// The defaults match [`AscParser::new`]:
+///
+/// ```
+/// use ascdump::{AscParser, AscParserOptions, NumericBase};
+///
+/// let options = AscParserOptions::default()
+///     .base(NumericBase::Dec)
+///     .strict(true);
+/// let input = "0.962604 3 872 Rx d 4 204 85 1 0\nnot a frame\n1.0 3 872 Rx d 1 0";
+/// let frames: Vec<_> = AscParser::with_options(input.as_bytes(), options).collect();
+/// assert_eq!(frames.len(), 1);
+/// assert_eq!(frames[0].id, 0x368);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AscParserOptions {
+    allow_short_payload: bool,
+    rebase_trigger_blocks: bool,
+    base: Option<NumericBase>,
+    strict: bool,
+    comment_prefixes: Vec<String>,
+    yield_events: bool,
+    payload_separators: bool,
+}
+
+impl Default for AscParserOptions {
+    fn default() -> Self {
+        Self {
+            allow_short_payload: false,
+            rebase_trigger_blocks: false,
+            base: None,
+            strict: false,
+            comment_prefixes: vec![String::from("//")],
+            yield_events: false,
+            payload_separators: false,
+        }
+    }
+}
+
+impl AscParserOptions {
+    /// Sets whether frames with fewer payload bytes than their length states are accepted,
+    /// see [`AscParser::allow_short_payload`]
+    pub fn allow_short_payload(mut self, allow: bool) -> Self {
+        self.allow_short_payload = allow;
+        self
+    }
+
+    /// Sets whether timestamps are re-based at `Begin Triggerblock` markers, see
+    /// [`AscParser::with_trigger_block_rebase`]
+    pub fn rebase_trigger_blocks(mut self, rebase: bool) -> Self {
+        self.rebase_trigger_blocks = rebase;
+        self
+    }
+
+    /// Forces the numeric base of ids and payload bytes, ignoring the `base` header line
+    pub fn base(mut self, base: NumericBase) -> Self {
+        self.base = Some(base);
+        self
+    }
+
+    /// Sets whether parsing ends at the first line that is not a frame instead of skipping it
+    ///
+    /// Iterating [`AscParser::try_frames`] yields the error of that line before ending.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Sets the prefixes marking comment lines, which are skipped without parsing them
+    ///
+    /// Leading whitespace is ignored. Defaults to `//`, which Vector tools use. Comment lines
+    /// are neither reported by [`AscParser::try_frames`] nor stop a strict parser.
+    pub fn comment_prefixes<I, S>(mut self, prefixes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.comment_prefixes = prefixes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets whether [`AscParser::entries`] yields event lines like `Start of measurement` as
+    /// [`AscEntry::Event`]
+    ///
+    /// Event lines are skipped otherwise. They are never reported as errors by
+    /// [`AscParser::try_frames`] and do not stop a strict parser.
+    pub fn yield_events(mut self, yield_events: bool) -> Self {
+        self.yield_events = yield_events;
+        self
+    }
+
+    /// Sets whether payloads may be enclosed in brackets or separated by commas, e.g.
+    /// `[CC, 55, 01, 00]`
+    ///
+    /// Some tools other than Vector's write such payloads to `.asc` files. Payloads using them
+    /// are copied without these characters before decoding, so the default of rejecting them
+    /// avoids checking for them.
+    pub fn payload_separators(mut self, allow: bool) -> Self {
+        self.payload_separators = allow;
+        self
+    }
+
+    fn is_comment(&self, line: &str) -> bool {
+        let line = line.trim_start();
+        self.comment_prefixes
+            .iter()
+            .any(|prefix| line.starts_with(prefix.as_str()))
+    }
+}
+
+impl CanFrame {
+    /// Parses a line using the numeric base declared in the given header
+    fn parse_with_header(s: &str, header: &AscHeader) -> Result<Self, AscParseError> {
+        Self::parse_with_options(s, header, &AscParserOptions::default())
+    }
+
+    fn parse_with_options(
+        s: &str,
+        header: &AscHeader,
+        options: &AscParserOptions,
+    ) -> Result<Self, AscParseError> {
+        let (mut frame, payload_text) = Self::parse_fields(s, header)?;
+        let payload_text = match options.payload_separators && payload_text.contains(SEPARATORS) {
+            true => Cow::Owned(payload_text.replace(SEPARATORS, " ")),
+            false => Cow::Borrowed(payload_text),
+        };
+
+        // The offset behind the payload, unknown if the payload was cut short
+        let mut payload_end = Some(0);
+        // Remote frames request data, they state a length but carry no payload
+        if !frame.is_rtr {
+            let mut payload = Vec::with_capacity(frame.length);
+            let scanned = scan_payload(&payload_text, frame.length, header.base.radix(), |byte| {
+                payload.push(byte)
+            });
+            payload_end = match scanned {
+                // A truncated payload ends at the first token that is not a byte, e.g. `Length`
+                Err(AscParseError::InvalidPayload { .. })
+                | Err(AscParseError::InvalidLengthField { .. })
+                    if options.allow_short_payload =>
+                {
+                    None
+                }
+                scanned => {
+                    let (count, end) = scanned?;
+                    (count == frame.length).then_some(end)
+                }
+            };
+            if options.allow_short_payload {
+                payload.resize(frame.length, 0);
+            }
+            frame.payload = payload;
+            check_payload_length(frame.length, frame.payload.len())?;
+        }
+        if frame.is_fd {
+            frame.fd_flags = payload_end.and_then(|end| parse_fd_flags(&payload_text[end..]));
+        }
+
+        Ok(frame)
+    }
+
+    /// Parses all fields of a line except for the payload, returning the frame without payload
+    /// and the remainder of the line following the length field
+    fn parse_fields<'a>(s: &'a str, header: &AscHeader) -> Result<(Self, &'a str), AscParseError> {
+        Self::locate_fields(s, header).map(|(frame, fields)| (frame, fields.remainder))
+    }
+
+    /// Parses all fields of a line except for the payload like [`CanFrame::parse_fields`],
+    /// returning the tokens the fields were read from as well
+    fn locate_fields<'a>(
+        s: &'a str,
+        header: &AscHeader,
+    ) -> Result<(Self, Fields<'a>), AscParseError> {
+        // Lines may still carry their terminator, which is CRLF in files exported on Windows
+        let s = s.trim_end_matches(['\n', '\r']);
+        if s.trim().is_empty() {
+            return Err(AscParseError::EmptyLine);
+        }
+        let radix = header.base.radix();
+        let mut frame = Self::new();
+        let mut tokens = s.split_whitespace();
+        let can_fd = is_fd_line(s);
+        frame.is_fd = can_fd;
+        let mut fields = Fields::default();
+
+        if let Some(timestamp_token) = tokens.next() {
+            fields.timestamp = timestamp_token;
+            frame.timestamp = parse_timestamp(timestamp_token, header)?;
+        } else {
+            return Err(AscParseError::InvalidFormat { str: s.to_string() });
+        }
+
+        // Classic lines of single channel logs may lack the channel column, so the id is directly
+        // followed by the direction. The bus then defaults to 0.
+        let has_bus = can_fd || {
+            let mut lookahead = tokens.clone().skip(1);
+            !matches!(lookahead.next(), Some("Rx") | Some("Tx"))
+        };
+
+        if !has_bus {
+            frame.bus_id = 0;
+        } else if let Some(bus_id_token) = match can_fd {
+            true => tokens.nth(1),
+            false => tokens.next(),
+        } {
+            fields.bus_id = Some(bus_id_token);
+            frame.bus_id =
+                u16::from_str(bus_id_token).map_err(|err| AscParseError::InvalidBusId {
+                    str: err.to_string(),
+                })?;
+        } else {
+            return Err(AscParseError::InvalidFormat { str: s.to_string() });
+        }
+
+        // CANFD lines carry the direction in front of the id
+        if can_fd {
+            if let Some(direction_token) = tokens.next() {
+                frame.direction = Direction::from_str(direction_token)?;
+            } else {
+                return Err(AscParseError::InvalidFormat { str: s.to_string() });
+            }
+        }
+
+        if let Some(id_token) = tokens.next() {
+            fields.id = id_token;
+            // Extended ids carry a single 'x' suffix, anything else has to be a number
+            let digits = id_token.strip_suffix('x');
+            frame.is_extended = digits.is_some();
+            frame.id = u32::from_str_radix(digits.unwrap_or(id_token), radix).map_err(|err| {
+                AscParseError::InvalidFrameId {
+                    str: err.to_string(),
+                }
+            })?;
+        } else {
+            return Err(AscParseError::InvalidFormat { str: s.to_string() });
+        }
+
+        // Data frames are marked with 'd', remote frames with 'r'. Depending on the logger, the
+        // direction precedes or follows that marker, the length comes after both.
+        if !can_fd {
+            let (mut has_direction, mut has_type) = (false, false);
+            while !(has_direction && has_type) {
+                match tokens.next() {
+                    Some("d") if !has_type => has_type = true,
+                    Some("r") if !has_type => {
+                        frame.is_rtr = true;
+                        has_type = true;
+                    }
+                    Some(token) if !has_direction => {
+                        frame.direction = Direction::from_str(token)?;
+                        has_direction = true;
+                    }
+                    _ => return Err(AscParseError::InvalidFormat { str: s.to_string() }),
+                }
+            }
+        }
+
+        // CANFD lines carry the bit rate switch and error state indicator flags next
+        if can_fd {
+            frame.brs = parse_flag(tokens.next(), s)?;
+            frame.esi = parse_flag(tokens.next(), s)?;
+
+            if let Some(dlc_token) = tokens.next() {
+                fields.dlc = dlc_token;
+                frame.dlc = parse_fd_dlc(dlc_token)?;
+            } else {
+                return Err(AscParseError::InvalidFormat { str: s.to_string() });
+            }
+        }
+
+        if let Some(length_token) = tokens.next() {
+            if !can_fd {
+                fields.dlc = length_token;
+            }
+            let length =
+                usize::from_str(length_token).map_err(|err| AscParseError::InvalidLengthField {
+                    str: err.to_string(),
+                })?;
+            frame.length = match can_fd {
+                // Some exports write the raw DLC into the data length column as well
+                true if length == frame.dlc as usize => dlc_to_length(frame.dlc),
+                true => length,
+                // Longer payloads hint at corruption or a CAN FD frame not marked as such
+                false if length > 8 => {
+                    return Err(AscParseError::InvalidLengthField {
+                        str: format!(
+                            "{} exceeds the maximum of 8 bytes of classic CAN, the line may be \
+                             a CANFD frame",
+                            length
+                        ),
+                    })
+                }
+                false => {
+                    // Classic frames only state the DLC, which equals the data length
+                    frame.dlc = length as u8;
+                    length
+                }
+            };
+            if frame.length > 64 {
+                return Err(AscParseError::InvalidLengthField {
+                    str: format!("{} exceeds the maximum of 64 bytes", frame.length),
+                });
+            }
+            let remainder = remainder_after(s, length_token);
+            let (frame_length_bits, bit_count) = parse_trailing_metadata(remainder);
+            frame.frame_length_bits = frame_length_bits;
+            frame.bit_count = bit_count;
+            fields.remainder = remainder;
+            Ok((frame, fields))
+        } else {
+            Err(AscParseError::InvalidFormat { str: s.to_string() })
+        }
+    }
+}
+
+/// Extracts the `Length = ` and `BitCount = ` fields trailing the payload of classic lines
+///
+/// CANFD lines state these values positionally and yield `None` for both.
+fn parse_trailing_metadata(s: &str) -> (Option<u32>, Option<u32>) {
+    let mut frame_length_bits = None;
+    let mut bit_count = None;
+    let mut tokens = s.split_whitespace();
+    while let Some(token) = tokens.next() {
+        let field = match token {
+            "Length" => &mut frame_length_bits,
+            "BitCount" => &mut bit_count,
+            _ => continue,
+        };
+        if let (Some("="), Some(value)) = (tokens.next(), tokens.next()) {
+            *field = u32::from_str(value).ok();
+        }
+    }
+    (frame_length_bits, bit_count)
+}
+
+/// Characters enclosing or separating payload bytes, see [`AscParserOptions::payload_separators`]
+const SEPARATORS: [char; 3] = ['[', ']', ','];
+
+fn check_payload_length(exp: usize, act: usize) -> Result<(), AscParseError> {
+    match exp == act {
+        true => Ok(()),
+        false => Err(AscParseError::InvalidPayloadLength { exp, act }),
+    }
+}
+
+const SECONDS_PER_DAY: f64 = 24.0 * 3600.0;
+
+/// Parses the timestamp of a line, which is written as clock time in some logs with absolute
+/// timestamps
+fn parse_timestamp(token: &str, header: &AscHeader) -> Result<f64, AscParseError> {
+    match token.contains(':') {
+        true if header.timestamps == TimestampMode::Absolute => clock_since_start(token, header),
+        _ => f64::from_str(token).map_err(|err| AscParseError::InvalidTimestamp {
+            str: err.to_string(),
+        }),
+    }
+}
+
+/// Converts a timestamp written as clock time, e.g. `10:11:13.307604`, into seconds since the
+/// measurement start declared in the header
+///
+/// Without a `date` line in the header, the measurement is taken to start at midnight. Clock
+/// times before the start are taken to be on the next day.
+fn clock_since_start(token: &str, header: &AscHeader) -> Result<f64, AscParseError> {
+    let clock = parse_clock(token).ok_or_else(|| AscParseError::InvalidTimestamp {
+        str: format!("invalid clock time {:?}", token),
+    })?;
+    let since_start = clock - header.start_of_day().unwrap_or(0.0);
+    Ok(match since_start < 0.0 {
+        true => since_start + SECONDS_PER_DAY,
+        false => since_start,
+    })
+}
+
+/// The timestamp of an event or error frame line, `None` for any other line
+fn event_timestamp(line: &str, header: &AscHeader) -> Option<f64> {
+    Event::parse_with_header(line, header)
+        .map(|event| event.timestamp)
+        .or_else(|| {
+            let error = ErrorFrame::parse_with_header(line, header).ok()?;
+            Some(error.timestamp)
+        })
+}
+
+/// Whether the line is a `CANFD` line, which has the keyword right after the timestamp
+fn is_fd_line(line: &str) -> bool {
+    line.split_whitespace().nth(1) == Some("CANFD")
+}
+
+/// The tokens of a line the fields of a frame were parsed from, see [`ParsedLine`]
+#[derive(Default)]
+struct Fields<'a> {
+    timestamp: &'a str,
+    bus_id: Option<&'a str>,
+    id: &'a str,
+    /// The DLC of CANFD lines, the length of classic lines
+    dlc: &'a str,
+    /// The rest of the line following the length, starting with the payload
+    remainder: &'a str,
+}
+
+/// Extracts the `Flags` field of CANFD lines from the part of the line following the payload,
+/// in which it is the third token
+///
+/// The field is written in hex regardless of the numeric base. Lines of loggers ending after
+/// the payload yield `None`.
+fn parse_fd_flags(s: &str) -> Option<FdFlags> {
+    s.split_whitespace()
+        .nth(2)
+        .and_then(|token| u32::from_str_radix(token, 16).ok())
+        .map(FdFlags)
+}
+
+/// Returns the part of the line following the given token, which must be a slice of the line
+fn remainder_after<'a>(line: &'a str, token: &str) -> &'a str {
+    let offset = token.as_ptr() as usize - line.as_ptr() as usize + token.len();
+    &line[offset..]
+}
+
+/// Decodes up to `length` whitespace separated payload bytes by scanning the line directly,
+/// returning the number of bytes decoded and the offset behind the last one
+///
+/// This avoids splitting the line into string tokens for every byte. Tokens that are not plain
+/// digits of the given radix are handed to `u8::from_str_radix`, so errors are reported the
+/// same way as for the other fields. Hex bytes need to be written with exactly two digits.
+fn scan_payload(
+    s: &str,
+    length: usize,
+    radix: u32,
+    mut push: impl FnMut(u8),
+) -> Result<(usize, usize), AscParseError> {
+    let bytes = s.as_bytes();
+    let mut count = 0;
+    let mut pos = 0;
+
+    while count < length {
+        while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+        }
+        if pos == bytes.len() {
+            break;
+        }
+
+        let start = pos;
+        let mut value: Option<u32> = Some(0);
+        while pos < bytes.len() && !bytes[pos].is_ascii_whitespace() {
+            value = value
+                .zip((bytes[pos] as char).to_digit(radix))
+                .map(|(value, digit)| value * radix + digit)
+                .filter(|value| *value <= u8::MAX as u32);
+            pos += 1;
+        }
+
+        let token = &s[start..pos];
+        let byte = match value {
+            Some(value) if radix != 16 || token.len() == 2 => value as u8,
+            // Running into the trailing metadata of classic lines means the length is off
+            _ if token == "Length" => {
+                return Err(AscParseError::InvalidLengthField {
+                    str: format!(
+                        "{} stated, but the payload ends after {} bytes at the trailing Length \
+                         field",
+                        length, count
+                    ),
+                })
+            }
+            _ => match u8::from_str_radix(token, radix) {
+                Ok(byte) if radix != 16 => byte,
+                // Hex bytes take two digits, a single one most likely is a byte cut short
+                Ok(_) => {
+                    return Err(AscParseError::InvalidPayload {
+                        str: format!("expected two hex digits, found {:?}", token),
+                    })
+                }
+                Err(err) => {
+                    return Err(AscParseError::InvalidPayload {
+                        str: err.to_string(),
+                    })
+                }
+            },
+        };
+        push(byte);
+        count += 1;
+    }
+
+    Ok((count, pos))
+}
+
+/// Maps a CAN FD data length code to the number of payload bytes it denotes
+///
+/// Codes 0 to 8 map linearly, codes 9 to 15 map to 12, 16, 20, 24, 32, 48 and 64 bytes. Codes
+/// beyond 15 are not defined and saturate at 64 bytes.
+pub fn dlc_to_length(dlc: u8) -> usize {
+    match dlc {
+        0..=8 => dlc as usize,
+        9 => 12,
+        10 => 16,
+        11 => 20,
+        12 => 24,
+        13 => 32,
+        14 => 48,
+        _ => 64,
+    }
+}
+
+/// Parses the DLC column of a CANFD line, which is written as a single hex digit or in decimal
+fn parse_fd_dlc(token: &str) -> Result<u8, AscParseError> {
+    u8::from_str_radix(token, 16)
+        .ok()
+        .filter(|dlc| *dlc <= 15)
+        .or_else(|| u8::from_str(token).ok().filter(|dlc| *dlc <= 15))
+        .ok_or_else(|| AscParseError::InvalidDlc {
+            str: token.to_string(),
+        })
+}
+
+fn parse_flag(token: Option<&str>, line: &str) -> Result<bool, AscParseError> {
+    match token {
+        Some("0") => Ok(false),
+        Some("1") => Ok(true),
+        Some(flag_token) => Err(AscParseError::InvalidFlag {
+            str: flag_token.to_string(),
+        }),
+        None => Err(AscParseError::InvalidFormat {
+            str: line.to_string(),
+        }),
+    }
+}
+
+impl FromStr for ErrorFrame {
+    type Err = AscParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_header(s, &AscHeader::default())
+    }
+}
+
+impl ErrorFrame {
+    /// Parses a line using the timestamp format declared in the given header
+    fn parse_with_header(s: &str, header: &AscHeader) -> Result<Self, AscParseError> {
+        let mut tokens = s.split_whitespace();
+        let can_fd = is_fd_line(s);
+
+        let timestamp = match tokens.next() {
+            Some(timestamp_token) => parse_timestamp(timestamp_token, header)?,
+            None => return Err(AscParseError::InvalidFormat { str: s.to_string() }),
+        };
+
+        let bus_id = match match can_fd {
+            true => tokens.nth(1),
+            false => tokens.next(),
+        } {
+            Some(bus_id_token) => {
+                u16::from_str(bus_id_token).map_err(|err| AscParseError::InvalidBusId {
+                    str: err.to_string(),
+                })?
+            }
+            None => return Err(AscParseError::InvalidFormat { str: s.to_string() }),
+        };
+
+        // CANFD error frames carry the direction in front of the keyword
+        if let Some("ErrorFrame") = match can_fd {
+            true => tokens.nth(1),
+            false => tokens.next(),
+        } {
+            Ok(Self { timestamp, bus_id })
+        } else {
+            Err(AscParseError::InvalidFormat { str: s.to_string() })
+        }
+    }
+}
+
+impl FromStr for AscEntry {
+    type Err = AscParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with_header(s, &AscHeader::default())
+    }
+}
+
+impl AscEntry {
+    fn parse_with_header(s: &str, header: &AscHeader) -> Result<Self, AscParseError> {
+        Self::parse_with_options(s, header, &AscParserOptions::default())
+    }
+
+    fn parse_with_options(
+        s: &str,
+        header: &AscHeader,
+        options: &AscParserOptions,
+    ) -> Result<Self, AscParseError> {
+        if s.split_whitespace().any(|token| token == "ErrorFrame") {
+            return ErrorFrame::parse_with_header(s, header).map(AscEntry::Error);
+        }
+        match CanFrame::parse_with_options(s, header, options) {
+            Err(err) if options.yield_events => Event::parse_with_header(s, header)
+                .map(AscEntry::Event)
+                .ok_or(err),
+            result => result.map(AscEntry::Data),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests {
+
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn parse_can_frame_from_string_timestamp() {
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(0.962604, frame.timestamp);
+
+        let invalid =
+            String::from("0.9xxxxx 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        assert_eq!(true, CanFrame::from_str(&invalid).is_err());
+
+        let invalid_length = String::from("");
+        assert_eq!(true, CanFrame::from_str(&invalid_length).is_err());
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_large_timestamp() {
+        let line =
+            String::from("86400.123456 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.timestamp, 86400.123456);
+        assert_eq!(format!("{:.6}", frame.timestamp), "86400.123456");
+        assert_eq!(frame.to_asc_line(), line);
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_bus_id() {
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.bus_id, 3);
+
+        let invalid =
+            String::from("0.962604 _ 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        assert_eq!(true, CanFrame::from_str(&invalid).is_err());
+
+        let invalid_length = String::from("0.962604");
+        assert_eq!(true, CanFrame::from_str(&invalid_length).is_err());
+
+        let line_canfd =
+            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(frame.bus_id, 1);
+
+        let line_many_channels =
+            String::from("0.962604 312 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line_many_channels).expect("Uncaught error while parsing");
+        assert_eq!(frame.bus_id, 312);
+
+        let overflow =
+            String::from("0.962604 70000 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        assert!(matches!(
+            CanFrame::from_str(&overflow),
+            Err(AscParseError::InvalidBusId { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_can_id() {
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.id, 0x368);
+
+        let invalid =
+            String::from("0.962604 3 3_8 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        assert_eq!(true, CanFrame::from_str(&invalid).is_err());
+
+        let invalid_length = String::from("0.962604 3");
+        assert_eq!(true, CanFrame::from_str(&invalid_length).is_err());
+
+        let line_canfd =
+            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(frame.id, 0x6e);
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_extended_can_id() {
+        let line =
+            String::from("0.962892 3 1f78c410x Rx d 8 02 00 00 00 24 00 70 03 Length = 0 BitCount = 0 ID = 528008208x");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.id, 0x1f78c410);
+        assert!(frame.is_extended);
+
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.id, 0x368);
+        assert!(!frame.is_extended);
+
+        let line_canfd = String::from(
+            "7.392600 CANFD 1 Rx 12b80210x 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0",
+        );
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(frame.id, 0x12b80210);
+        assert!(frame.is_extended);
+    }
+
+    #[test]
+    fn parse_direction_on_either_side_of_frame_type() {
+        let direction_first = CanFrame::from_str(
+            "0.962604 3 368 Tx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872",
+        )
+        .expect("Uncaught error while parsing");
+        let type_first = CanFrame::from_str(
+            "0.962604 3 368 d Tx 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872",
+        )
+        .expect("Uncaught error while parsing");
+        assert_eq!(type_first, direction_first);
+        assert_eq!(type_first.direction, Direction::Tx);
+        assert_eq!(type_first.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+
+        let remote = CanFrame::from_str("1.0 1 123 r Rx 8").expect("Uncaught error while parsing");
+        assert!(remote.is_rtr);
+        assert_eq!(remote.direction, Direction::Rx);
+        assert_eq!(remote.length, 8);
+    }
+
+    #[test]
+    fn parse_clock_time_timestamps() {
+        let input = "date Mon Sep 14 10:11:12.5 am 2020\n\
+                     base hex  timestamps absolute\n\
+                     0.807604 3 368 Rx d 1 01\n\
+                     10:11:13.307604 3 368 Rx d 1 02\n\
+                     10:11:14 3 368 Rx d 1 03\n";
+        let mut parser = AscParser::new(input.as_bytes());
+        let frames: Vec<CanFrame> = parser.by_ref().collect();
+        assert_eq!(frames.len(), 3);
+        assert!((frames[0].timestamp - 0.807604).abs() < 1e-9);
+        assert!((frames[1].timestamp - 0.807604).abs() < 1e-6);
+        assert!((frames[2].timestamp - 1.5).abs() < 1e-6);
+        assert!((frames[1].time_of_day(parser.header()) - 36673.307604).abs() < 1e-6);
+
+        // Without a date line, clock times count from midnight
+        let frame = CanFrame::from_str("00:01:02.5 1 123 Rx d 1 01").unwrap();
+        assert_eq!(frame.timestamp, 62.5);
+        assert_eq!(frame.time_of_day(&AscHeader::default()), 62.5);
+        assert!(CanFrame::from_str("00:61:02 1 123 Rx d 1 01").is_err());
+
+        let mut header = AscHeader::default();
+        header.parse_line("base hex  timestamps relative");
+        assert!(CanFrame::parse_with_header("00:01:02.5 1 123 Rx d 1 01", &header).is_err());
+    }
+
+    #[test]
+    fn parse_clock_time_error_frames_and_events() {
+        let input = "date Mon Sep 14 10:11:12.5 am 2020\n\
+                     base hex  timestamps absolute\n\
+                     10:11:12.5 Start of measurement\n\
+                     10:11:13 3 368 Rx d 1 01\n\
+                     10:11:13.5 1 ErrorFrame\n\
+                     10:11:14 3 368 Rx d 1 02\n";
+        let options = AscParserOptions::default().strict(true);
+        let frames: Vec<CanFrame> = AscParser::with_options(input.as_bytes(), options).collect();
+        assert_eq!(frames.len(), 2);
+
+        let options = AscParserOptions::default().yield_events(true);
+        let entries: Vec<AscEntry> = AscParser::with_options(input.as_bytes(), options)
+            .entries()
+            .collect();
+        assert_eq!(entries.len(), 4);
+        assert!(matches!(&entries[0], AscEntry::Event(event) if event.timestamp == 0.0));
+        assert_eq!(
+            entries[2],
+            AscEntry::Error(ErrorFrame {
+                timestamp: 1.0,
+                bus_id: 1
+            })
+        );
+    }
+
+    #[test]
+    fn yield_event_lines() {
+        let input = "0.000000 Start of measurement\n\
+                     0.5 1 123 Rx d 1 01\n\
+                     0.7 log trigger event\n\
+                     0.8 CAN 1 Status:chip status error active\n\
+                     0.9 1 123 Rx d 1 02\n";
+
+        let options = AscParserOptions::default().strict(true);
+        let frames: Vec<CanFrame> = AscParser::with_options(input.as_bytes(), options.clone())
+            .try_frames()
+            .collect::<Result<_, _>>()
+            .expect("Event lines are not errors");
+        assert_eq!(frames.len(), 2);
+
+        let entries: Vec<AscEntry> = AscParser::with_options(input.as_bytes(), options.clone())
+            .entries()
+            .collect();
+        assert_eq!(entries.len(), 2);
+
+        let entries: Vec<AscEntry> =
+            AscParser::with_options(input.as_bytes(), options.yield_events(true))
+                .entries()
+                .collect();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(
+            entries[0],
+            AscEntry::Event(Event {
+                timestamp: 0.0,
+                kind: EventKind::StartOfMeasurement,
+                text: String::from("Start of measurement"),
+            })
+        );
+        assert!(matches!(entries[1], AscEntry::Data(_)));
+        assert!(matches!(
+            &entries[2],
+            AscEntry::Event(Event { timestamp, kind: EventKind::Trigger, .. }) if *timestamp == 0.7
+        ));
+        assert!(matches!(
+            &entries[3],
+            AscEntry::Event(Event {
+                kind: EventKind::Status,
+                ..
+            })
+        ));
+
+        assert_eq!(Event::parse_line("0.5 1 123 Rx d 1 01"), None);
+        assert_eq!(Event::parse_line("what a trigger"), None);
+    }
+
+    #[test]
+    fn skip_comment_lines() {
+        let input = "// exported by a Vector tool\n\
+                     # recorded on the test bench\n\
+                     0.5 1 123 Rx d 1 01\n\
+                     \t# bus 2 is idle\n\
+                     0.6 1 123 Rx d 1 02\n";
+
+        let frames: Vec<_> = AscParser::new(input.as_bytes()).try_frames().collect();
+        assert_eq!(frames.len(), 4);
+        assert!(matches!(frames[0], Err(LocatedError { line: 2, .. })));
+
+        let options = AscParserOptions::default()
+            .comment_prefixes(vec!["//", "#"])
+            .strict(true);
+        let frames: Vec<_> = AscParser::with_options(input.as_bytes(), options)
+            .try_frames()
+            .collect::<Result<_, _>>()
+            .expect("Comment lines are not errors");
+        assert_eq!(frames.len(), 2);
+    }
+
+    #[test]
+    fn collect_statistic_footer() {
+        let input = "0.5 1 123 Rx d 1 01\n\
+                     0.6 1 123 Rx d 1 02\n\
+                     1.0 1 Statistic: D 2 R 0 XD 0 XR 0 E 1 O 0 B 0.03%\n";
+        let mut frames = AscParser::new(input.as_bytes()).try_frames();
+        assert_eq!(frames.by_ref().filter(Result::is_err).count(), 0);
+
+        let statistics = frames.statistics();
+        assert_eq!(statistics.len(), 1);
+        assert_eq!(statistics[0].bus_id, Some(1));
+        assert_eq!(statistics[0].data_frames, 2);
+        assert_eq!(statistics[0].error_frames, 1);
+        assert_eq!(statistics[0].bus_load, 0.03);
+    }
+
+    #[test]
+    fn sort_frames_in_time_order() {
+        let mut frames: Vec<CanFrame> = [
+            "1.5 1 123 Rx d 1 03",
+            "0.5 2 123 Rx d 1 02",
+            "1.5 1 100 Rx d 1 04",
+            "0.5 1 200 Rx d 1 01",
+            "2.0 1 123 Rx d 1 05",
+        ]
+        .iter()
+        .map(|line| CanFrame::from_str(line).unwrap())
+        .collect();
+
+        frames.sort();
+        let payloads: Vec<u8> = frames.iter().map(|frame| frame.payload[0]).collect();
+        assert_eq!(payloads, vec![0x01, 0x02, 0x04, 0x03, 0x05]);
+        assert_eq!(frames.binary_search(&frames[3].clone()), Ok(3));
+    }
+
+    #[test]
+    fn capture_raw_frame_type_marker() {
+        let frame = CanFrame::from_str("0.962604 3 368 Rx d 4 cc 55 01 00").unwrap();
+        assert_eq!(frame.raw_flags(), "d");
+
+        let remote = CanFrame::from_str("0.5 1 123 Tx r 8").unwrap();
+        assert_eq!(remote.raw_flags(), "r");
+
+        let fd = CanFrame::from_str(
+            "7.392600 CANFD 1 Rx 6e 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0",
+        )
+        .unwrap();
+        assert_eq!(fd.raw_flags(), "1 0");
+    }
+
+    #[test]
+    fn parse_from_buffered_input() {
+        let input = "base hex  timestamps absolute\n0.1 1 100 Rx d 1 01\n0.2 1 101 Rx d 2 02 03\n";
+        let reader = BufReader::with_capacity(16, std::io::Cursor::new(input));
+
+        let mut parser = AscParser::from_bufread(reader);
+        let frames: Vec<CanFrame> = parser.by_ref().collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].payload, vec![0x02, 0x03]);
+
+        parser.seek_to_timestamp(0.2).expect("Failed to seek");
+        assert_eq!(parser.next().map(|frame| frame.id), Some(0x101));
+    }
+
+    #[test]
+    fn parse_payloads_with_separators() {
+        let input = "0.1 1 100 Rx d 4 [CC 55 01 00] Length = 0 BitCount = 0 ID = 256\n\
+                     0.2 1 100 Rx d 4 CC,55,01,00\n\
+                     0.3 1 100 Rx d 4 [cc, 55, 01, 00]\n\
+                     7.392600 CANFD 1 Rx 6e 1 0 2 2 [ec,0a] 0 0 3000 0 0 0 0 0\n";
+
+        assert_eq!(AscParser::new(input.as_bytes()).count(), 0);
+
+        let options = AscParserOptions::default().payload_separators(true);
+        let frames: Vec<CanFrame> = AscParser::with_options(input.as_bytes(), options).collect();
+        assert_eq!(frames.len(), 4);
+        for frame in &frames[..3] {
+            assert_eq!(frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+        }
+        assert_eq!(frames[0].frame_length_bits, Some(0));
+        assert_eq!(frames[3].payload, vec![0xEC, 0x0A]);
+    }
+
+    #[test]
+    fn reject_single_nibble_payload_tokens() {
+        let expected = |token: &str| {
+            Err(AscParseError::InvalidPayload {
+                str: format!("expected two hex digits, found {:?}", token),
+            })
+        };
+        assert_eq!(
+            CanFrame::from_str("0.962604 3 368 Rx d 4 cc 55 1 00"),
+            expected("1")
+        );
+        assert_eq!(
+            CanFrame::from_str("0.962604 3 368 Rx d 1 +c"),
+            expected("+c")
+        );
+        assert_eq!(
+            CanFrame::from_str("0.962604 3 368 Rx d 1 0cc"),
+            expected("0cc")
+        );
+        assert_eq!(
+            CanFrame::from_str("0.962604 3 368 Rx d 1 1")
+                .unwrap_err()
+                .to_string(),
+            r#"Cannot parse payload "expected two hex digits, found \"1\"""#
+        );
+
+        let decimal = "base dec  timestamps absolute\n0.962604 3 872 Rx d 2 1 204\n";
+        let frames: Vec<CanFrame> = AscParser::new(decimal.as_bytes()).collect();
+        assert_eq!(frames[0].payload, vec![1, 204]);
+    }
+
+    #[test]
+    fn reject_empty_lines() {
+        assert_eq!(CanFrame::from_str(""), Err(AscParseError::EmptyLine));
+        assert_eq!(CanFrame::from_str("   "), Err(AscParseError::EmptyLine));
+        assert_eq!(CanFrame::from_str("\t\r\n"), Err(AscParseError::EmptyLine));
+
+        let input = "0.5 1 123 Rx d 1 01\n\n   \n0.6 1 123 Rx d 1 02\n";
+        let options = AscParserOptions::default().strict(true);
+        let frames: Vec<CanFrame> = AscParser::with_options(input.as_bytes(), options)
+            .try_frames()
+            .collect::<Result<_, _>>()
+            .expect("Empty lines are not errors");
+        assert_eq!(frames.len(), 2);
+    }
+
+    #[test]
+    fn parse_line_from_bytes() {
+        let line: &[u8] = b"0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\r\n";
+        let frame = CanFrame::try_from(line).expect("Uncaught error while parsing");
+        assert_eq!(frame.id, 0x368);
+        assert_eq!(frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+
+        let invalid: &[u8] = b"0.962604 3 368 Rx d 1 \xcc";
+        assert!(matches!(
+            CanFrame::try_from(invalid),
+            Err(AscParseError::InvalidEncoding { .. })
+        ));
+        let non_ascii = "0.962604 3 368 Rx d 1 \u{e4}";
+        assert!(matches!(
+            CanFrame::try_from(non_ascii.as_bytes()),
+            Err(AscParseError::InvalidPayload { .. })
+        ));
+    }
+
+    #[test]
+    fn reject_dlc_running_into_trailing_metadata() {
+        let line = "0.962604 3 368 Rx d 5 cc 55 01 00 Length = 0 BitCount = 0 ID = 872";
+        assert_eq!(
+            CanFrame::from_str(line),
+            Err(AscParseError::InvalidLengthField {
+                str: String::from(
+                    "5 stated, but the payload ends after 4 bytes at the trailing Length field"
+                )
+            })
+        );
+
+        // Without trailing metadata the payload is merely short
+        assert_eq!(
+            CanFrame::from_str("0.962604 3 368 Rx d 5 cc 55 01 00"),
+            Err(AscParseError::InvalidPayloadLength { exp: 5, act: 4 })
+        );
+    }
+
+    #[test]
+    fn parse_line_without_from_str() {
+        let line = "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872";
+        let frame = parse_line(line).expect("Uncaught error while parsing");
+        assert_eq!(frame.id, 0x368);
+        assert_eq!(frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+        assert_eq!(Ok(frame), CanFrame::from_str(line));
+
+        assert!(parse_line("0.962604 3").is_err());
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_single_extended_suffix() {
+        let frame = CanFrame::from_str("0.5 1 368 Rx d 1 01").unwrap();
+        assert_eq!((frame.id, frame.is_extended), (0x368, false));
+
+        let frame = CanFrame::from_str("0.5 1 368x Rx d 1 01").unwrap();
+        assert_eq!((frame.id, frame.is_extended), (0x368, true));
+
+        assert!(matches!(
+            CanFrame::from_str("0.5 1 36xx Rx d 1 01"),
+            Err(AscParseError::InvalidFrameId { .. })
+        ));
+        assert!(CanFrame::from_str("0.5 1 3x6 Rx d 1 01").is_err());
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_direction() {
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.direction, Direction::Rx);
+
+        let line =
+            String::from("0.962604 3 368 Tx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.direction, Direction::Tx);
+
+        let invalid =
+            String::from("0.962604 3 368 Xx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        assert_eq!(
+            CanFrame::from_str(&invalid),
+            Err(AscParseError::InvalidDirection {
+                str: String::from("Xx")
+            })
+        );
+
+        let invalid_length = String::from("0.962604 3 368");
+        assert!(CanFrame::from_str(&invalid_length).is_err());
+
+        assert!(CanFrame::from_str("0.962604 3 368 Rx Tx d 1 01").is_err());
+        assert!(CanFrame::from_str("0.962604 3 368 d d Rx 1 01").is_err());
+
+        let line_canfd =
+            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(frame.direction, Direction::Rx);
+
+        let line_canfd =
+            String::from("7.392600 CANFD 1 Tx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(frame.direction, Direction::Tx);
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_payload() {
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.length, 4);
+        assert_eq!(frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+
+        let invalid_length_field =
+            String::from("0.962604 3 368 Rx d _ cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        assert_eq!(true, CanFrame::from_str(&invalid_length_field).is_err());
+
+        let invalid_payload =
+            String::from("0.962604 3 368 Rx d 4 cc 55 __ 00 Length = 0 BitCount = 0 ID = 872");
+        assert_eq!(true, CanFrame::from_str(&invalid_payload).is_err());
+
+        let invalid_length_1 = String::from("0.962604 3 368 Rx d");
+        assert_eq!(true, CanFrame::from_str(&invalid_length_1).is_err());
+
+        let invalid_length_2 = String::from("0.962604 3 368 Rx d 4 cc");
+        assert_eq!(true, CanFrame::from_str(&invalid_length_2).is_err());
+
+        let line_canfd =
+            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(frame.length, 6);
+        assert_eq!(frame.payload, vec![0xEC, 0x0A, 0x22, 0xFF, 0xFF, 0xF1]);
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_fd_flags() {
+        let line_canfd =
+            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert!(frame.brs);
+        assert!(!frame.esi);
+
+        let line_canfd =
+            String::from("7.392600 CANFD 1 Rx 6e   0 1 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert!(!frame.brs);
+        assert!(frame.esi);
+
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert!(!frame.brs);
+        assert!(!frame.esi);
+
+        let invalid =
+            String::from("7.392600 CANFD 1 Rx 6e   2 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        assert_eq!(
+            CanFrame::from_str(&invalid),
+            Err(AscParseError::InvalidFlag {
+                str: String::from("2")
+            })
+        );
+    }
+
+    #[test]
+    fn decode_fd_flags_field() {
+        let line =
+            "15.185424 CANFD 2 Tx 1a1x 0 1 f 64 00 01 02 03 04 05 06 07 08 09 0a 0b 0c 0d 0e \
+                    0f 10 11 12 13 14 15 16 17 18 19 1a 1b 1c 1d 1e 1f 20 21 22 23 24 25 26 27 28 \
+                    29 2a 2b 2c 2d 2e 2f 30 31 32 33 34 35 36 37 38 39 3a 3b 3c 3d 3e 3f \
+                    130130 1199 405000 2a5ffc 00000000 00000000 00000000 00000000";
+        let frame = CanFrame::from_str(line).expect("Uncaught error while parsing");
+        let flags = frame.fd_flags.expect("Missing flags");
+        assert_eq!(flags, FdFlags(0x405000));
+        assert!(flags.edl());
+        assert!(!flags.brs());
+        assert!(flags.esi());
+        assert_eq!(flags.esi(), frame.esi);
+
+        let frame = CanFrame::from_str("7.392600 CANFD 1 Rx 6e 1 0 6 6 ec 0a 22 ff ff f1")
+            .expect("Uncaught error while parsing");
+        assert_eq!(frame.fd_flags, None);
+
+        let line = "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872";
+        let frame = CanFrame::from_str(line).expect("Uncaught error while parsing");
+        assert_eq!(frame.fd_flags, None);
+    }
+
+    #[test]
+    fn locate_fd_flags_behind_the_payload() {
+        let header = AscHeader::default();
+        let options = AscParserOptions::default().payload_separators(true);
+        for line in [
+            "7.392600 CANFD 1 Rx 6e 1 0 6 6 ec,0a,22,ff,ff,f1 0 0 3000 0 0 0 0 0",
+            "7.392600 CANFD 1 Rx 6e 1 0 6 6 [ ec 0a 22 ff ff f1 ] 0 0 3000 0 0 0 0 0",
+            "7.392600 CANFD 1 Rx 6e 1 0 6 6 [ec,0a,22,ff,ff,f1] 0 0 3000 0 0 0 0 0",
+        ] {
+            let frame = CanFrame::parse_with_options(line, &header, &options)
+                .expect("Uncaught error while parsing");
+            assert_eq!(frame.fd_flags, Some(FdFlags(0x3000)));
+        }
+
+        // The flags cannot be told apart from the payload of a short line
+        let options = AscParserOptions::default().allow_short_payload(true);
+        let frame = CanFrame::parse_with_options(
+            "7.392600 CANFD 1 Rx 6e 1 0 6 6 ec 0a 22 ff 0 0 3000 0 0 0 0 0",
+            &header,
+            &options,
+        )
+        .expect("Uncaught error while parsing");
+        assert_eq!(frame.payload, vec![0xEC, 0x0A, 0x22, 0xFF, 0, 0]);
+        assert_eq!(frame.fd_flags, None);
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_dlc() {
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.dlc, 4);
+        assert_eq!(frame.length, 4);
+
+        let payload = ["aa"; 64].join(" ");
+        let line_canfd = format!(
+            "7.392600 CANFD 1 Rx 6e 1 0 f 64 {} 0 0 3000 0 0 0 0 0",
+            payload
+        );
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(frame.dlc, 15);
+        assert_eq!(frame.length, 64);
+        assert_eq!(frame.payload, vec![0xAA; 64]);
+
+        let line_canfd = format!(
+            "7.392600 CANFD 1 Rx 6e 1 0 15 15 {} 0 0 3000 0 0 0 0 0",
+            payload
+        );
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(frame.dlc, 15);
+        assert_eq!(frame.length, 64);
+
+        let payload = ["aa"; 12].join(" ");
+        let line_canfd = format!(
+            "7.392600 CANFD 1 Rx 6e 1 0 9 12 {} 0 0 3000 0 0 0 0 0",
+            payload
+        );
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(frame.dlc, 9);
+        assert_eq!(frame.length, 12);
+
+        let invalid =
+            String::from("7.392600 CANFD 1 Rx 6e   1 0 g 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        assert!(CanFrame::from_str(&invalid).is_err());
+    }
+
+    #[test]
+    fn dlc_to_length_table() {
+        let lengths: Vec<usize> = (0..=15).map(dlc_to_length).collect();
+        assert_eq!(
+            lengths,
+            vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64]
+        );
+    }
+
+    #[test]
+    fn parse_can_frame_from_string_rtr() {
+        let line = String::from("1.234567 1 123 Rx r 8 Length = 0 BitCount = 0 ID = 291");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert!(frame.is_rtr);
+        assert_eq!(frame.id, 0x123);
+        assert_eq!(frame.length, 8);
+        assert!(frame.payload.is_empty());
+
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert!(!frame.is_rtr);
+
+        let invalid =
+            String::from("0.962604 3 368 Rx q 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        assert!(CanFrame::from_str(&invalid).is_err());
+    }
+
+    #[test]
+    fn parse_error_frame_from_string() {
+        let line = String::from("1.234567 3 ErrorFrame");
+        let entry = AscEntry::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(
+            entry,
+            AscEntry::Error(ErrorFrame {
+                timestamp: 1.234567,
+                bus_id: 3
+            })
+        );
+
+        let line_canfd = String::from("2.5 CANFD 1 Rx ErrorFrame 0 0 0 0 0 0 0 0 0");
+        let entry = AscEntry::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(
+            entry,
+            AscEntry::Error(ErrorFrame {
+                timestamp: 2.5,
+                bus_id: 1
+            })
+        );
+
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let entry = AscEntry::from_str(&line).expect("Uncaught error while parsing");
+        assert!(matches!(entry, AscEntry::Data(_)));
+
+        let invalid = String::from("1.234567 _ ErrorFrame");
+        assert!(AscEntry::from_str(&invalid).is_err());
+    }
+
+    #[test]
+    fn iterate_over_entries() {
+        let lines = String::from(
+            "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            1.234567 3 ErrorFrame\n\
+            this is not a frame",
+        );
+
+        let mut entries = AscParser::new(lines.as_bytes()).entries();
+
+        assert!(matches!(entries.next(), Some(AscEntry::Data(_))));
+        assert_eq!(
+            entries.next(),
+            Some(AscEntry::Error(ErrorFrame {
+                timestamp: 1.234567,
+                bus_id: 3
+            }))
+        );
+        assert_eq!(entries.next(), None);
+    }
+
+    #[test]
+    fn display_can_frame_in_candump_format() {
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.to_string(), "can3 368 [4] CC 55 01 00");
+
+        let line =
+            String::from("0.962892 3 1f78c410x Rx d 8 02 00 00 00 24 00 70 03 Length = 0 BitCount = 0 ID = 528008208x");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(
+            frame.to_string(),
+            "can3 1F78C410 [8] 02 00 00 00 24 00 70 03"
+        );
+
+        let line = String::from("1.234567 1 12 Rx r 8 Length = 0 BitCount = 0 ID = 18");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.to_string(), "can1 012 [8]  remote request");
+    }
+
+    #[test]
+    fn serialize_can_frame_to_asc_line() {
+        let line =
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+        assert_eq!(frame.to_asc_line(), line);
+
+        let line_canfd =
+            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+        assert_eq!(
+            frame.to_asc_line(),
+            "7.392600 CANFD 1 Rx 6e 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0"
+        );
+    }
+
+    #[test]
+    fn serialize_can_frame_round_trip() {
+        let payload_64 = ["5a"; 64].join(" ");
+        let lines = vec![
+            String::from("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872"),
+            String::from("0.962892 3 1f78c410x Tx d 8 02 00 00 00 24 00 70 03 Length = 0 BitCount = 0 ID = 528008208x"),
+            String::from("1.234567 1 12 Rx r 8 Length = 0 BitCount = 0 ID = 18"),
+            String::from("2.000000 2 7ff Rx d 0 Length = 0 BitCount = 0 ID = 2047"),
+            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0"),
+            String::from("7.392600 CANFD 1 Tx 12b80210x 0 1 6 6 ec 0a 22 ff ff f1 0 0 5000 0 0 0 0 0"),
+            format!("8.5 CANFD 2 Rx 1a 1 0 f 64 {} 0 0 3000 0 0 0 0 0", payload_64),
+        ];
+
+        for line in lines {
+            let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+            let reparsed = CanFrame::from_str(&frame.to_asc_line())
+                .expect("Uncaught error while parsing serialized line");
+            assert_eq!(frame, reparsed);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_json_round_trip() {
+        let line =
+            String::from("0.962892 3 1f78c410x Rx d 8 02 00 00 00 24 00 70 03 Length = 0 BitCount = 0 ID = 528008208x");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+
+        let json = serde_json::to_string(&frame).expect("Failed to serialize frame");
+        assert!(json.contains("\"id\":528008208"));
+        assert!(json.contains("\"payload\":[2,0,0,0,36,0,112,3]"));
+
+        let deserialized: CanFrame = serde_json::from_str(&json).expect("Failed to deserialize");
+        assert_eq!(frame, deserialized);
+    }
+
+    fn decode_payload(s: &str, length: usize, radix: u32) -> Result<Vec<u8>, AscParseError> {
+        let mut payload = vec![];
+        scan_payload(s, length, radix, |byte| payload.push(byte)).map(|_| payload)
+    }
+
+    #[test]
+    fn decode_payload_like_token_parsing() {
+        let fixture = include_str!("../tests/fixtures/sample.asc");
+        let reference = |line: &str| -> Vec<u8> {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            let (length_index, length) = match tokens[1] {
+                "CANFD" => (8, usize::from_str(tokens[8]).unwrap()),
+                _ => (5, usize::from_str(tokens[5]).unwrap()),
+            };
+            tokens[length_index + 1..=length_index + length]
+                .iter()
+                .map(|token| u8::from_str_radix(token, 16).unwrap())
+                .collect()
+        };
+
+        let mut frames = 0;
+        for line in fixture.lines() {
+            if let Ok(frame) = CanFrame::from_str(line) {
+                assert_eq!(frame.payload, reference(line));
+                frames += 1;
+            }
+        }
+        assert_eq!(frames, 6);
+
+        assert_eq!(
+            decode_payload(" 01 02 ff", 3, 16),
+            Ok(vec![0x01, 0x02, 0xFF])
+        );
+        assert_eq!(decode_payload(" 1 +2 255", 3, 10), Ok(vec![1, 2, 255]));
+        assert_eq!(decode_payload(" 12 34 56", 2, 10), Ok(vec![12, 34]));
+        assert_eq!(
+            decode_payload(" 100", 1, 16),
+            Err(AscParseError::InvalidPayload {
+                str: u8::from_str_radix("100", 16).unwrap_err().to_string()
+            })
+        );
+        assert_eq!(
+            decode_payload(" 01 Length = 0", 2, 16),
+            Err(AscParseError::InvalidLengthField {
+                str: String::from(
+                    "2 stated, but the payload ends after 1 bytes at the trailing Length field"
+                )
+            })
+        );
+        assert_eq!(
+            decode_payload(" c_", 1, 16),
+            Err(AscParseError::InvalidPayload {
+                str: u8::from_str_radix("c_", 16).unwrap_err().to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn seek_to_timestamp() {
+        let mut lines = String::from("base dec  timestamps absolute\nBegin Triggerblock\n");
+        for i in 0..1000 {
+            lines.push_str(&format!(
+                "{}.{:03} 1 {} Rx d 1 {}\n",
+                i / 10,
+                i % 10 * 100,
+                i,
+                i % 256
+            ));
+            if i % 7 == 0 {
+                lines.push_str("// comment line\n");
+            }
+        }
+        let mut parser = AscParser::new(Cursor::new(lines.into_bytes()));
+
+        parser.seek_to_timestamp(42.25).unwrap();
+        let frame = parser.next().unwrap();
+        assert_eq!(frame.timestamp, 42.3);
+        assert_eq!(frame.id, 423);
+
+        parser.seek_to_timestamp(10.0).unwrap();
+        assert_eq!(parser.next().map(|frame| frame.id), Some(100));
+
+        parser.seek_to_timestamp(-1.0).unwrap();
+        assert_eq!(parser.next().map(|frame| frame.id), Some(0));
+
+        parser.seek_to_timestamp(100.0).unwrap();
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn deduplicate_frames_in_hash_set() {
+        use std::collections::HashSet;
+
+        let fixture = include_str!("../tests/fixtures/sample.asc");
+        let frames: Vec<CanFrame> = AscParser::new(fixture.as_bytes()).collect();
+
+        let mut unique: HashSet<CanFrame> = frames.iter().cloned().collect();
+        assert_eq!(unique.len(), 6);
+        unique.extend(frames.iter().cloned());
+        assert_eq!(unique.len(), 6);
+
+        let mut later = frames[0].clone();
+        later.timestamp += 1.0;
+        assert_ne!(later, frames[0]);
+        assert!(unique.insert(later.clone()));
+
+        // The timestamp is not part of the hash
+        let hash = |frame: &CanFrame| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            frame.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_eq!(hash(&later), hash(&frames[0]));
+    }
+
+    #[test]
+    fn iterate_with_raw_lines() {
+        let fixture = include_str!("../tests/fixtures/sample.asc");
+        let mut parser = AscParser::new(fixture.as_bytes());
+        assert_eq!(parser.peek().map(|frame| frame.id), Some(0x368));
+
+        let pairs: Vec<(CanFrame, String)> = parser.with_raw_lines().collect();
+        assert_eq!(pairs.len(), 6);
+        assert_eq!(
+            pairs[0].1,
+            "   0.962604 3  368             Rx   d 4 cc 55 01 00  Length = 0 BitCount = 0 ID = 872"
+        );
+        assert_eq!(pairs[1].0.id, 0x1F78C410);
+        assert_eq!(
+            pairs[1].1,
+            "   0.962892 3  1f78c410x       Rx   d 8 02 00 00 00 24 00 70 03  Length = 0 BitCount = 0 ID = 528008208x"
+        );
+        for (frame, line) in pairs {
+            assert_eq!(CanFrame::from_str(&line), Ok(frame));
+        }
+    }
+
+    #[test]
+    fn iterate_over_short_payloads() {
+        let lines = String::from(
+            "0.1 1 100 Rx d 8 01 02 03 04 05  Length = 0 BitCount = 0 ID = 256\n\
+            0.2 1 101 Rx d 2 01 02  Length = 0 BitCount = 0 ID = 257\n\
+            0.3 1 102 Rx d 4 01",
+        );
+
+        assert_eq!(
+            AscParser::new(lines.as_bytes()).try_frames().next(),
+            Some(Err(LocatedError {
+                line: 1,
+                source: AscParseError::InvalidLengthField {
+                    str: String::from(
+                        "8 stated, but the payload ends after 5 bytes at the trailing Length \
+                         field"
+                    )
+                }
+            }))
+        );
+
+        let frames: Vec<CanFrame> = AscParser::new(lines.as_bytes())
+            .allow_short_payload(true)
+            .collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].length, 8);
+        assert_eq!(frames[0].payload, vec![1, 2, 3, 4, 5, 0, 0, 0]);
+        assert_eq!(frames[1].payload, vec![1, 2]);
+        assert_eq!(frames[2].payload, vec![1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn parse_with_options() {
+        let input = "base hex  timestamps absolute\n\
+                     0.5 1 100 Rx d 2 10 20\n\
+                     0.6 1 100 Rx d 4 10 20\n\
+                     0.7 1 100 Rx d 2 30 40\n";
+
+        let frames: Vec<CanFrame> = AscParser::new(input.as_bytes()).collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].id, 0x100);
+
+        let options = AscParserOptions::default()
+            .base(NumericBase::Dec)
+            .strict(true);
+        let mut parser = AscParser::with_options(input.as_bytes(), options).try_frames();
+        let frame = parser.next().unwrap().unwrap();
+        assert_eq!(parser.header().base, NumericBase::Dec);
+        assert_eq!(frame.id, 100);
+        assert_eq!(frame.payload, vec![10, 20]);
+        assert_eq!(
+            parser.next(),
+            Some(Err(LocatedError {
+                line: 3,
+                source: AscParseError::InvalidPayloadLength { exp: 4, act: 2 }
+            }))
+        );
+        assert_eq!(parser.next(), None);
+
+        let options = AscParserOptions::default().allow_short_payload(true);
+        let frames: Vec<CanFrame> = AscParser::with_options(input.as_bytes(), options).collect();
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[1].payload, vec![0x10, 0x20, 0, 0]);
+    }
+
+    #[test]
+    fn parse_trailing_bit_counts() {
+        let frame = CanFrame::from_str(
+            "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 108000 BitCount = 56 ID = 872",
+        )
+        .unwrap();
+        assert_eq!(frame.frame_length_bits, Some(108000));
+        assert_eq!(frame.bit_count, Some(56));
+        assert_eq!(CanFrame::from_str(&frame.to_asc_line()), Ok(frame));
+
+        let remote =
+            CanFrame::from_str("0.5 1 123 Rx r 8 Length = 0 BitCount = 46 ID = 291").unwrap();
+        assert_eq!(remote.bit_count, Some(46));
+
+        let bare = CanFrame::from_str("0.962604 3 368 Rx d 2 cc 55").unwrap();
+        assert_eq!(bare.frame_length_bits, None);
+        assert_eq!(bare.bit_count, None);
+
+        let fd = CanFrame::from_str(
+            "7.392600 CANFD 1 Rx 6e 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0",
+        )
+        .unwrap();
+        assert_eq!(fd.frame_length_bits, None);
+        assert_eq!(fd.bit_count, None);
+    }
+
+    #[test]
+    fn iterate_over_crlf_lines() {
+        let fixture = include_str!("../tests/fixtures/sample.asc");
+        let crlf = fixture.replace('\n', "\r\n");
+        assert!(crlf.contains("\r\n"));
+
+        let frames: Vec<CanFrame> = AscParser::new(fixture.as_bytes()).collect();
+        let crlf_frames: Vec<CanFrame> = AscParser::new(crlf.as_bytes()).collect();
+        assert_eq!(crlf_frames.len(), 6);
+        assert_eq!(crlf_frames, frames);
+
+        let mut header = AscParser::new(crlf.as_bytes());
+        header.peek();
+        assert_eq!(
+            header.header().date.as_deref(),
+            Some("Mon Sep 14 10:11:12.345 am 2020")
+        );
+
+        for (line, expected) in [
+            ("0.962604 3 368 Rx d 2 cc 55\r\n", vec![0xCC, 0x55]),
+            ("0.962604 3 368 Rx d 2 cc 55\r", vec![0xCC, 0x55]),
+            (
+                "0.962604 3 368 Rx d 2 cc 55 Length = 0 BitCount = 7\r\n",
+                vec![0xCC, 0x55],
+            ),
+            ("0.5 1 123 Rx r 2\r\n", vec![]),
+        ]
+        .iter()
+        {
+            let frame = CanFrame::from_str(line).unwrap();
+            assert_eq!(&frame.payload, expected);
+            assert_eq!(frame, CanFrame::from_str(line.trim_end()).unwrap());
+        }
+    }
+
+    #[test]
+    fn iterate_over_tab_separated_lines() {
+        let fixture = include_str!("../tests/fixtures/tabs.asc");
+        let mut parser = AscParser::new(fixture.as_bytes());
+        let frames: Vec<CanFrame> = parser.by_ref().collect();
+        assert_eq!(
+            parser.header().date.as_deref(),
+            Some("Mon Sep 14 10:11:12.345 am 2020")
+        );
+        assert_eq!(frames.len(), 3);
+
+        assert_eq!(
+            frames[0],
+            CanFrame::from_str(
+                "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 64 ID = 872"
+            )
+            .unwrap()
+        );
+        assert_eq!(frames[0].bit_count, Some(64));
+        assert_eq!(frames[1].id, 0x123);
+        assert!(frames[1].is_extended);
+        assert_eq!(frames[1].direction, Direction::Tx);
+        assert_eq!(frames[1].payload, vec![0x11, 0x22]);
+        assert_eq!(
+            frames[2],
+            CanFrame::from_str(
+                "7.392600 CANFD 1 Rx 6e 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0"
+            )
+            .unwrap()
+        );
+        assert!(frames[2].brs);
+    }
+
+    #[test]
+    fn parse_line_without_channel() {
+        let frame =
+            CanFrame::from_str("0.962604 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872")
+                .unwrap();
+        assert_eq!(frame.bus_id, 0);
+        assert_eq!(frame.id, 0x368);
+        assert_eq!(frame.direction, Direction::Rx);
+        assert_eq!(frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+
+        let frame = CanFrame::from_str("0.962892 1f78c410x Tx d 2 02 00").unwrap();
+        assert_eq!(frame.bus_id, 0);
+        assert_eq!(frame.id, 0x1F78C410);
+        assert!(frame.is_extended);
+        assert_eq!(frame.direction, Direction::Tx);
+
+        let frame = CanFrame::from_str("0.962604 2 368 Rx d 1 cc").unwrap();
+        assert_eq!(frame.bus_id, 2);
+    }
+
+    #[test]
+    fn reject_overlong_payloads() {
+        let classic = format!("0.962604 3 368 Rx d 12 {}", ["aa"; 12].join(" "));
+        assert_eq!(
+            CanFrame::from_str(&classic),
+            Err(AscParseError::InvalidLengthField {
+                str: String::from(
+                    "12 exceeds the maximum of 8 bytes of classic CAN, the line may be a CANFD \
+                     frame"
+                )
+            })
+        );
+        assert!(CanFrame::from_str("0.962604 3 368 Rx d 300 aa").is_err());
+
+        let fd = format!(
+            "7.392600 CANFD 1 Rx 6e 1 0 f 100 {} 0 0 3000 0 0 0 0 0",
+            ["aa"; 100].join(" ")
+        );
+        assert_eq!(
+            CanFrame::from_str(&fd),
+            Err(AscParseError::InvalidLengthField {
+                str: String::from("100 exceeds the maximum of 64 bytes")
+            })
+        );
+
+        let fd = format!(
+            "7.392600 CANFD 1 Rx 6e 1 0 f 64 {} 0 0 3000 0 0 0 0 0",
+            ["aa"; 64].join(" ")
+        );
+        assert_eq!(CanFrame::from_str(&fd).map(|frame| frame.length), Ok(64));
+    }
+
+    #[test]
+    fn detect_fd_lines_by_keyword_position() {
+        let classic = CanFrame::from_str("0.5 1 123 Rx d 2 11 22 // forwarded as CANFD").unwrap();
+        assert!(!classic.is_fd);
+        assert_eq!(classic.payload, vec![0x11, 0x22]);
+
+        let fd =
+            CanFrame::from_str("7.392600 CANFD 1 Rx 6e 0 0 2 2 11 22 0 0 1000 0 0 0 0 0").unwrap();
+        assert!(fd.is_fd);
+        assert!(!fd.brs);
+        assert_eq!(fd.length, 2);
+        assert!(fd.to_asc_line().contains(" CANFD "));
+    }
+
+    #[test]
+    fn count_frames_without_decoding() {
+        let fixture = include_str!("../tests/fixtures/sample.asc");
+        let parsed = AscParser::new(fixture.as_bytes()).count();
+        assert!(parsed > 0);
+        assert_eq!(count_frames(fixture.as_bytes()), parsed);
+
+        assert_eq!(
+            count_frames("no frame\n0.1 1 100 Rx d 1 zz\n".as_bytes()),
+            1
+        );
+    }
+
+    #[test]
+    fn compare_content_ignoring_timestamp() {
+        let frame = CanFrame::from_str("0.5 1 368 Rx d 2 cc 55").unwrap();
+        let later = CanFrame::from_str("1.5 1 368 Tx d 2 cc 55").unwrap();
+        assert_ne!(frame, later);
+        assert!(frame.content_eq(&later));
+
+        let other_bus = CanFrame::from_str("0.5 2 368 Rx d 2 cc 55").unwrap();
+        let other_id = CanFrame::from_str("0.5 1 368x Rx d 2 cc 55").unwrap();
+        let other_payload = CanFrame::from_str("0.5 1 368 Rx d 2 cc 56").unwrap();
+        assert!(!frame.content_eq(&other_bus));
+        assert!(!frame.content_eq(&other_id));
+        assert!(!frame.content_eq(&other_payload));
+    }
+
+    #[test]
+    fn skip_consecutive_duplicates() {
+        let input = "0.1 1 100 Rx d 2 01 02\n\
+                     0.2 1 100 Rx d 2 01 02\n\
+                     0.3 1 100 Rx d 2 01 03\n\
+                     0.4 1 100 Rx d 2 01 02\n";
+        let frames: Vec<CanFrame> = AscParser::new(input.as_bytes())
+            .dedup_consecutive()
+            .collect();
+
+        let timestamps: Vec<f64> = frames.iter().map(|frame| frame.timestamp).collect();
+        assert_eq!(timestamps, vec![0.1, 0.3, 0.4]);
+    }
+
+    #[test]
+    fn iterate_over_rebased_frames() {
+        let fixture = include_str!("../tests/fixtures/sample.asc");
+        let frames: Vec<CanFrame> = AscParser::new(fixture.as_bytes()).collect();
+        let rebased: Vec<CanFrame> = AscParser::new(fixture.as_bytes()).rebased().collect();
+
+        assert_eq!(rebased.len(), frames.len());
+        assert_eq!(rebased[0].timestamp, 0.0);
+        for (frame, rebased) in frames.iter().zip(&rebased) {
+            let expected = frame.timestamp - frames[0].timestamp;
+            assert!((rebased.timestamp - expected).abs() < 1e-9);
+            assert_eq!(rebased.payload, frame.payload);
+        }
+    }
+
+    #[test]
+    fn iterate_over_lines() {
+        let lines = String::from(
+            "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0",
+        );
+
+        let mut parser = AscParser::new(lines.as_bytes());
+
+        assert_eq!(
+            parser.next(),
+            Some(CanFrame {
+                timestamp: 0.962604,
+                bus_id: 3,
+                id: 0x368,
+                is_extended: false,
+                direction: Direction::Rx,
+                is_rtr: false,
+                is_fd: false,
+                brs: false,
+                esi: false,
+                dlc: 4,
+                length: 4,
+                payload: vec![0xCC, 0x55, 0x01, 0x00],
+                frame_length_bits: Some(0),
+                bit_count: Some(0),
+                fd_flags: None,
+            })
+        );
+        assert_eq!(
             parser.next(),
             Some(CanFrame {
                 timestamp: 7.392600,
                 bus_id: 1,
                 id: 0x6e,
+                is_extended: false,
+                direction: Direction::Rx,
+                is_rtr: false,
+                is_fd: true,
+                brs: true,
+                esi: false,
+                dlc: 6,
                 length: 6,
-                payload: vec![0xEC, 0x0A, 0x22, 0xFF, 0xFF, 0xF1]
+                payload: vec![0xEC, 0x0A, 0x22, 0xFF, 0xFF, 0xF1],
+                frame_length_bits: None,
+                bit_count: None,
+                fd_flags: Some(FdFlags::EDL | FdFlags::BRS),
             })
         );
         assert_eq!(parser.next(), None);
     }
 
+    #[test]
+    fn iterate_over_many_non_frame_lines() {
+        let mut lines = "0.1 1 zz Rx d 1 01\n".repeat(100_000);
+        lines.push_str("0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+
+        let mut parser = AscParser::new(lines.as_bytes());
+        assert_eq!(parser.next().map(|frame| frame.id), Some(0x368));
+        assert_eq!(parser.next(), None);
+
+        let mut entries = AscParser::new(lines.as_bytes()).entries();
+        assert!(matches!(entries.next(), Some(AscEntry::Data(_))));
+        assert_eq!(entries.next(), None);
+    }
+
+    #[test]
+    fn iterate_over_lines_reporting_errors() {
+        let lines = String::from(
+            "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            0.962604 3 368 Rx d 4 cc 55 __ 00 Length = 0 BitCount = 0 ID = 872\n\
+            7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0\n\
+            0.962604 3 368 Rx d",
+        );
+
+        let mut frames = AscParser::new(lines.as_bytes()).try_frames();
+
+        assert_eq!(frames.next().map(|f| f.map(|f| f.id)), Some(Ok(0x368)));
+        assert!(matches!(
+            frames.next(),
+            Some(Err(LocatedError {
+                line: 2,
+                source: AscParseError::InvalidPayload { .. }
+            }))
+        ));
+        assert_eq!(frames.next().map(|f| f.map(|f| f.id)), Some(Ok(0x6e)));
+        assert!(matches!(
+            frames.next(),
+            Some(Err(LocatedError {
+                line: 4,
+                source: AscParseError::InvalidFormat { .. }
+            }))
+        ));
+        assert_eq!(frames.next(), None);
+    }
+
+    #[test]
+    fn collect_errors_while_parsing() {
+        let lines = String::from(
+            "base hex  timestamps absolute\n\
+            0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            0.962604 3 368 Rx d 4 cc 55 __ 00 Length = 0 BitCount = 0 ID = 872\n\
+            Begin Triggerblock\n\
+            1.000000 1 123 Tx d 2 11 22\n\
+            1.100000 1 123 Tx d 3 11 22\n\
+            1.200000 1 123 Tx d 2 11 23\n\
+            End TriggerBlock\n\
+            7.392600 CANFD 1 Xx 6e 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0\n",
+        );
+
+        let mut errors = vec![];
+        let frames: Vec<CanFrame> =
+            parse_with_errors(lines.as_bytes(), |line, error| errors.push((line, error))).collect();
+
+        assert_eq!(frames.len(), 3);
+        assert_eq!(
+            frames.iter().map(|f| f.id).collect::<Vec<_>>(),
+            vec![0x368, 0x123, 0x123]
+        );
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(
+            errors[0],
+            (3, AscParseError::InvalidPayload { .. })
+        ));
+        assert_eq!(
+            errors[1],
+            (6, AscParseError::InvalidPayloadLength { exp: 3, act: 2 })
+        );
+        assert!(matches!(
+            errors[2],
+            (9, AscParseError::InvalidDirection { .. })
+        ));
+    }
+
+    #[test]
+    fn collect_all_frames() {
+        let fixture = include_str!("../tests/fixtures/second.asc");
+        let frames = CanFrame::collect_all(fixture.as_bytes()).unwrap();
+        assert_eq!(frames.len(), 2);
+
+        let broken = fixture.replace(
+            "End TriggerBlock",
+            "   0.300000 2  202             Rx   d 2 03\nEnd TriggerBlock",
+        );
+        let (frames, errors) = CanFrame::collect_all(broken.as_bytes()).unwrap_err();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(
+            errors,
+            vec![(7, AscParseError::InvalidPayloadLength { exp: 2, act: 1 })]
+        );
+    }
+
+    #[test]
+    fn parse_errors_report_line_number() {
+        let lines = String::from(
+            "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            0.962604 3 368 Rx d 4 cc",
+        );
+
+        let error = AscParser::new(lines.as_bytes())
+            .try_frames()
+            .find_map(Result::err)
+            .expect("Expected a parse error");
+
+        assert_eq!(error.line, 2);
+        assert_eq!(
+            error.source,
+            AscParseError::InvalidPayloadLength { exp: 4, act: 1 }
+        );
+        assert!(error.to_string().starts_with("line 2: "));
+    }
+
+    #[test]
+    fn iterate_over_lines_with_hex_header() {
+        let lines = String::from(
+            "date Mon Sep 14 10:11:12.345 am 2020\n\
+            base hex  timestamps absolute\n\
+            0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872",
+        );
+
+        let mut parser = AscParser::new(lines.as_bytes());
+        let frame = parser.next().expect("Expected a frame");
+
+        assert_eq!(parser.header().base, NumericBase::Hex);
+        assert_eq!(frame.id, 0x368);
+        assert_eq!(frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+    }
+
+    #[test]
+    fn iterate_over_lines_with_dec_header() {
+        let lines = String::from(
+            "base dec  timestamps absolute\n\
+            0.962604 3 872 Rx d 4 204 85 1 0 Length = 0 BitCount = 0 ID = 872\n\
+            0.962892 3 528008208x Rx d 2 2 36 Length = 0 BitCount = 0 ID = 528008208x",
+        );
+
+        let mut frames = AscParser::new(lines.as_bytes()).try_frames();
+
+        let frame = frames
+            .next()
+            .expect("Expected a frame")
+            .expect("Parse error");
+        assert_eq!(frames.header().base, NumericBase::Dec);
+        assert_eq!(frame.id, 0x368);
+        assert_eq!(frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+
+        let frame = frames
+            .next()
+            .expect("Expected a frame")
+            .expect("Parse error");
+        assert_eq!(frame.id, 0x1f78c410);
+        assert!(frame.is_extended);
+        assert_eq!(frame.payload, vec![0x02, 0x24]);
+    }
+
+    #[test]
+    fn iterate_over_lines_with_relative_timestamps() {
+        let lines = String::from(
+            "base hex  timestamps relative\n\
+            0.5 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            0.25 3 ErrorFrame\n\
+            0.25 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872",
+        );
+
+        // The error frame in between is a delta as well
+        let timestamps: Vec<f64> = AscParser::new(lines.as_bytes())
+            .map(|frame| frame.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![0.5, 1.0]);
+
+        let timestamps: Vec<f64> = AscParser::new(lines.as_bytes())
+            .try_frames()
+            .map(|frame| frame.unwrap().timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![0.5, 1.0]);
+
+        let timestamps: Vec<f64> = AscParser::new(lines.as_bytes())
+            .with_raw_lines()
+            .map(|(frame, _)| frame.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![0.5, 1.0]);
+
+        let timestamps: Vec<f64> = AscParser::new(lines.as_bytes())
+            .entries()
+            .map(|entry| match entry {
+                AscEntry::Data(frame) => frame.timestamp,
+                AscEntry::Error(error) => error.timestamp,
+                AscEntry::Event(event) => event.timestamp,
+            })
+            .collect();
+        assert_eq!(timestamps, vec![0.5, 0.75, 1.0]);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn iterate_over_lines_with_absolute_time() {
+        let lines = String::from(
+            "date Mon Sep 14 10:11:12.345 am 2020\n\
+            base hex  timestamps absolute\n\
+            2.000000 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872",
+        );
+
+        let mut parser = AscParser::new(lines.as_bytes());
+        let frame = parser.next().expect("Expected a frame");
+        assert_eq!(
+            parser.absolute_time(&frame).map(|t| t.to_string()),
+            Some(String::from("2020-09-14 10:11:14.345"))
+        );
+    }
+
+    #[test]
+    fn iterate_over_trigger_blocks() {
+        let lines = String::from(
+            "date Mon Sep 14 10:11:12.345 am 2020\n\
+            base hex  timestamps absolute\n\
+            Begin Triggerblock Mon Sep 14 10:11:12.345 am 2020\n\
+            1.000000 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            2.000000 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            End TriggerBlock\n\
+            Begin Triggerblock Mon Sep 14 10:11:20.345 am 2020\n\
+            0.500000 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            End TriggerBlock",
+        );
+
+        let mut frames = AscParser::new(lines.as_bytes()).try_frames();
+        let timestamps: Vec<f64> = frames
+            .by_ref()
+            .map(|frame| frame.expect("Unexpected parse error").timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![1.0, 2.0, 0.5]);
+
+        let timestamps: Vec<f64> = AscParser::with_trigger_block_rebase(lines.as_bytes(), true)
+            .map(|frame| frame.timestamp)
+            .collect();
+        assert_eq!(timestamps, vec![1.0, 2.0, 2.5]);
+    }
+
+    #[test]
+    fn peek_at_next_frame() {
+        let lines = String::from(
+            "base hex  timestamps absolute\n\
+            not a frame\n\
+            0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872\n\
+            7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0",
+        );
+
+        let mut parser = AscParser::new(lines.as_bytes());
+
+        assert_eq!(parser.peek().map(|frame| frame.id), Some(0x368));
+        assert_eq!(parser.peek().map(|frame| frame.id), Some(0x368));
+        assert_eq!(parser.next().map(|frame| frame.id), Some(0x368));
+
+        assert_eq!(parser.peek().map(|frame| frame.timestamp), Some(7.3926));
+        let mut frames = parser.try_frames();
+        assert_eq!(frames.next().map(|f| f.map(|f| f.id)), Some(Ok(0x6e)));
+        assert_eq!(frames.next(), None);
+    }
+
     #[test]
     fn iterate_over_lines_with_bus_filter() {
         let lines = String::from(
@@ -306,8 +3330,18 @@ mod tests {
                 timestamp: 7.392600,
                 bus_id: 1,
                 id: 0x6e,
+                is_extended: false,
+                direction: Direction::Rx,
+                is_rtr: false,
+                is_fd: true,
+                brs: true,
+                esi: false,
+                dlc: 6,
                 length: 6,
-                payload: vec![0xEC, 0x0A, 0x22, 0xFF, 0xFF, 0xF1]
+                payload: vec![0xEC, 0x0A, 0x22, 0xFF, 0xFF, 0xF1],
+                frame_length_bits: None,
+                bit_count: None,
+                fd_flags: Some(FdFlags::EDL | FdFlags::BRS),
             })
         );
         assert_eq!(parser.next(), None);
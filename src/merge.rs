@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use std::io::Read;
+
+use crate::{AscParser, CanFrame};
+
+/// Interleaves the frames of several logs in time order
+///
+/// Each input is expected to be sorted by timestamp already. Frames with equal timestamps are
+/// ordered by bus id, remaining ties keep the order of the inputs.
+pub fn merge<R: Read>(parsers: Vec<AscParser<R>>) -> AscMerger<R> {
+    let mut heads = Vec::with_capacity(parsers.len());
+    let mut inputs = Vec::with_capacity(parsers.len());
+    for mut parser in parsers {
+        heads.push(parser.next());
+        inputs.push(parser);
+    }
+    AscMerger { inputs, heads }
+}
+
+/// An iterator merging frames from several parsers, see [`merge`]
+pub struct AscMerger<R: Read> {
+    inputs: Vec<AscParser<R>>,
+    heads: Vec<Option<CanFrame>>,
+}
+
+impl<R> Iterator for AscMerger<R>
+where
+    R: Read,
+{
+    type Item = CanFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut earliest: Option<(usize, &CanFrame)> = None;
+        for (index, head) in self.heads.iter().enumerate() {
+            if let Some(frame) = head {
+                let is_earlier = match earliest {
+                    None => true,
+                    Some((_, current)) => {
+                        (frame.timestamp, frame.bus_id) < (current.timestamp, current.bus_id)
+                    }
+                };
+                if is_earlier {
+                    earliest = Some((index, frame));
+                }
+            }
+        }
+
+        let (index, _) = earliest?;
+        let next = self.inputs[index].next();
+        std::mem::replace(&mut self.heads[index], next)
+    }
+}
+
+/// Rewrites the bus id of each frame according to the mapping, e.g. before merging logs
+///
+/// Buses without an entry in the mapping pass through unchanged.
+pub fn remap_bus<I>(frames: I, mapping: HashMap<u16, u16>) -> RemapBus<I::IntoIter>
+where
+    I: IntoIterator<Item = CanFrame>,
+{
+    RemapBus {
+        frames: frames.into_iter(),
+        mapping,
+    }
+}
+
+/// An iterator rewriting bus ids, see [`remap_bus`]
+pub struct RemapBus<I> {
+    frames: I,
+    mapping: HashMap<u16, u16>,
+}
+
+impl<I> Iterator for RemapBus<I>
+where
+    I: Iterator<Item = CanFrame>,
+{
+    type Item = CanFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut frame = self.frames.next()?;
+        if let Some(bus_id) = self.mapping.get(&frame.bus_id) {
+            frame.bus_id = *bus_id;
+        }
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn merge_logs_in_time_order() {
+        let first = "0.1 1 100 Rx d 1 01\n0.3 1 100 Rx d 1 03\n0.5 1 100 Rx d 1 05\n";
+        let second = "0.2 2 200 Rx d 1 02\n0.3 0 200 Rx d 1 04\n0.6 2 200 Rx d 1 06\n";
+
+        let frames: Vec<CanFrame> = merge(vec![
+            AscParser::new(first.as_bytes()),
+            AscParser::new(second.as_bytes()),
+        ])
+        .collect();
+
+        assert_eq!(frames.len(), 6);
+        assert!(frames
+            .windows(2)
+            .all(|pair| pair[0].timestamp <= pair[1].timestamp));
+        let payloads: Vec<u8> = frames.iter().map(|frame| frame.payload[0]).collect();
+        assert_eq!(payloads, vec![0x01, 0x02, 0x04, 0x03, 0x05, 0x06]);
+    }
+
+    #[test]
+    fn remap_bus_before_merging() {
+        let first = "0.1 1 100 Rx d 1 01\n0.3 2 100 Rx d 1 03\n";
+        let second = "0.2 1 200 Rx d 1 02\n";
+
+        let mapping: HashMap<u16, u16> = vec![(1, 5)].into_iter().collect();
+        let remapped = remap_bus(AscParser::new(second.as_bytes()), mapping);
+        let buses: Vec<u16> = merge(vec![AscParser::new(first.as_bytes())])
+            .chain(remapped)
+            .map(|frame| frame.bus_id)
+            .collect();
+        assert_eq!(buses, vec![1, 2, 5]);
+    }
+
+    #[test]
+    fn merge_without_inputs() {
+        let parsers: Vec<AscParser<&[u8]>> = vec![];
+        assert_eq!(merge(parsers).count(), 0);
+    }
+}
@@ -0,0 +1,134 @@
+use std::io::Read;
+
+use rayon::prelude::*;
+
+use crate::{event_timestamp, AscHeader, BusStatistics, CanFrame, TimestampMode};
+
+/// Parses the whole input in parallel, yielding the same frames as iterating an
+/// [`AscParser`](crate::AscParser)
+///
+/// The input is read into memory and its lines are parsed on the rayon thread pool. Header
+/// lines are taken into account as long as they precede the first frame, which is where ASC
+/// writers put them. Trigger blocks are not re-based.
+pub fn par_parse<R: Read>(mut reader: R) -> Vec<CanFrame> {
+    let mut bytes = Vec::new();
+    // Like the sequential parser, stop at the first read error and keep what was read until then
+    let _ = reader.read_to_end(&mut bytes);
+    let input = complete_lines(&bytes);
+
+    let header = leading_header(input);
+    if header.timestamps == TimestampMode::Absolute {
+        return input
+            .par_lines()
+            .filter_map(|line| CanFrame::parse_with_header(line, &header).ok())
+            .collect();
+    }
+
+    // Relative timestamps are deltas to the previous line, be it a frame or not
+    let lines: Vec<Result<CanFrame, f64>> = input
+        .par_lines()
+        .filter_map(|line| match CanFrame::parse_with_header(line, &header) {
+            Ok(frame) => Some(Ok(frame)),
+            Err(_) => non_frame_timestamp(line, &header).map(Err),
+        })
+        .collect();
+
+    let mut elapsed = 0.0;
+    lines
+        .into_iter()
+        .filter_map(|line| match line {
+            Ok(mut frame) => {
+                elapsed += frame.timestamp;
+                frame.timestamp = elapsed;
+                Some(frame)
+            }
+            Err(timestamp) => {
+                elapsed += timestamp;
+                None
+            }
+        })
+        .collect()
+}
+
+/// The timestamp of a line holding no frame, but an event, error frame or bus statistics
+fn non_frame_timestamp(line: &str, header: &AscHeader) -> Option<f64> {
+    event_timestamp(line, header).or_else(|| BusStatistics::parse_line(line)?.timestamp)
+}
+
+/// Returns the input up to the first line that is not valid UTF-8
+fn complete_lines(bytes: &[u8]) -> &str {
+    match std::str::from_utf8(bytes) {
+        Ok(input) => input,
+        Err(err) => {
+            let valid = &bytes[..err.valid_up_to()];
+            let end = valid
+                .iter()
+                .rposition(|byte| *byte == b'\n')
+                .map_or(0, |pos| pos + 1);
+            std::str::from_utf8(&valid[..end]).unwrap_or_default()
+        }
+    }
+}
+
+/// Collects the header lines in front of the first frame
+fn leading_header(input: &str) -> AscHeader {
+    let mut header = AscHeader::default();
+    for line in input.lines() {
+        if !header.parse_line(line) && CanFrame::parse_with_header(line, &header).is_ok() {
+            break;
+        }
+    }
+    header
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::AscParser;
+
+    #[test]
+    fn parse_fixture_in_parallel() {
+        let fixture = include_str!("../tests/fixtures/sample.asc");
+        let sequential: Vec<CanFrame> = AscParser::new(fixture.as_bytes()).collect();
+        let parallel = par_parse(fixture.as_bytes());
+        assert_eq!(parallel.len(), 6);
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn parse_large_input_in_parallel() {
+        let mut input = String::from("base dec  timestamps relative\n");
+        for i in 0..10_000 {
+            input.push_str(&format!("0.5 1 {} Rx d 2 {} 0\n", i % 2048, i % 256));
+        }
+
+        let sequential: Vec<CanFrame> = AscParser::new(input.as_bytes()).collect();
+        let parallel = par_parse(input.as_bytes());
+        assert_eq!(parallel.len(), 10_000);
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel[9_999].timestamp, 5000.0);
+    }
+
+    #[test]
+    fn count_deltas_of_non_frame_lines() {
+        let input = "base hex  timestamps relative\n\
+                     0.1 1 100 Rx d 1 01\n\
+                     0.1 1 ErrorFrame\n\
+                     0.1 Start of measurement\n\
+                     0.1 1 100 Rx d 1 02\n";
+        let sequential: Vec<CanFrame> = AscParser::new(input.as_bytes()).collect();
+        let parallel = par_parse(input.as_bytes());
+        assert_eq!(parallel, sequential);
+        assert_eq!(parallel[1].timestamp, 0.4);
+    }
+
+    #[test]
+    fn stop_at_invalid_utf8() {
+        let mut input = b"0.1 1 100 Rx d 1 01\n0.2 1 101 Rx d 1 02\n".to_vec();
+        input.extend_from_slice(b"0.3 1 102 Rx d 1 \xff\n0.4 1 103 Rx d 1 04\n");
+        let frames = par_parse(input.as_slice());
+        assert_eq!(frames.len(), 2);
+        assert_eq!(AscParser::new(input.as_slice()).count(), 2);
+    }
+}
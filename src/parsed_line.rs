@@ -0,0 +1,131 @@
+use std::ops::Range;
+
+use crate::{check_payload_length, scan_payload, AscHeader, AscParseError, CanFrame};
+
+/// A frame along with the byte ranges of its fields within the line it was parsed from
+///
+/// Some loggers append columns of their own to the standard fields. Knowing where the standard
+/// fields end, such columns can be read from [`ParsedLine::rest`] without parsing the whole line
+/// again.
+///
+/// ```
+/// use ascdump::ParsedLine;
+///
+/// let parsed = ParsedLine::parse("0.962604 3 368 Rx d 2 cc 55 gateway").unwrap();
+/// assert_eq!(parsed.field(&parsed.id), "368");
+/// assert_eq!(parsed.rest().trim(), "gateway");
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParsedLine<'a> {
+    pub line: &'a str,
+    pub frame: CanFrame,
+    pub timestamp: Range<usize>,
+    /// The bus column, which classic lines of single channel logs may lack
+    pub channel: Option<Range<usize>>,
+    pub id: Range<usize>,
+    /// The DLC column of CANFD lines, the length column of classic lines
+    pub dlc: Range<usize>,
+    /// The payload bytes, an empty range behind the DLC for remote frames
+    pub payload: Range<usize>,
+}
+
+impl<'a> ParsedLine<'a> {
+    /// Parses a line of an ASC file without a header, i.e. with hex ids and payload
+    pub fn parse(line: &'a str) -> Result<Self, AscParseError> {
+        Self::parse_with_header(line, &AscHeader::default())
+    }
+
+    /// Parses a line using the numeric base declared in the given header
+    pub fn parse_with_header(line: &'a str, header: &AscHeader) -> Result<Self, AscParseError> {
+        let (mut frame, fields) = CanFrame::locate_fields(line, header)?;
+        let payload_start = span(line, fields.remainder).start;
+
+        let payload = match frame.is_rtr {
+            // Remote frames request data, they state a length but carry no payload
+            true => payload_start..payload_start,
+            false => {
+                let mut payload = Vec::with_capacity(frame.length);
+                let (count, end) = scan_payload(
+                    fields.remainder,
+                    frame.length,
+                    header.base.radix(),
+                    |byte| payload.push(byte),
+                )?;
+                check_payload_length(frame.length, count)?;
+                frame.payload = payload;
+                let text = &fields.remainder[..end];
+                let start = payload_start + (text.len() - text.trim_start().len());
+                start..payload_start + end
+            }
+        };
+
+        Ok(Self {
+            line,
+            frame,
+            timestamp: span(line, fields.timestamp),
+            channel: fields.bus_id.map(|bus_id| span(line, bus_id)),
+            id: span(line, fields.id),
+            dlc: span(line, fields.dlc),
+            payload,
+        })
+    }
+
+    /// The text of a field, e.g. `parsed.field(&parsed.id)`
+    pub fn field(&self, range: &Range<usize>) -> &'a str {
+        &self.line[range.clone()]
+    }
+
+    /// The rest of the line following the payload, e.g. the `Length = ` fields
+    pub fn rest(&self) -> &'a str {
+        &self.line[self.payload.end..]
+    }
+}
+
+/// The byte range of a token within the line it was split from
+fn span(line: &str, token: &str) -> Range<usize> {
+    let start = token.as_ptr() as usize - line.as_ptr() as usize;
+    start..start + token.len()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn locate_fields_of_classic_line() {
+        let line = "0.962604 3  368 Rx d 4 cc 55 01 00  Length = 0 BitCount = 0 ID = 872 gw";
+        let parsed = ParsedLine::parse(line).unwrap();
+
+        assert_eq!(parsed.timestamp, 0..8);
+        assert_eq!(parsed.channel, Some(9..10));
+        assert_eq!(parsed.id, 12..15);
+        assert_eq!(parsed.dlc, 21..22);
+        assert_eq!(parsed.payload, 23..34);
+        assert_eq!(parsed.field(&parsed.payload), "cc 55 01 00");
+        assert_eq!(parsed.rest().split_whitespace().last(), Some("gw"));
+        assert_eq!(parsed.frame.payload, vec![0xCC, 0x55, 0x01, 0x00]);
+
+        let parsed = ParsedLine::parse("0.5 123 Tx r 8 extra").unwrap();
+        assert_eq!(parsed.channel, None);
+        assert_eq!(parsed.field(&parsed.id), "123");
+        assert_eq!(parsed.payload, 14..14);
+        assert_eq!(parsed.rest(), " extra");
+    }
+
+    #[test]
+    fn locate_fields_of_fd_line() {
+        let line = "7.392600 CANFD 1 Rx 6e 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0";
+        let parsed = ParsedLine::parse(line).unwrap();
+
+        assert_eq!(parsed.field(&parsed.timestamp), "7.392600");
+        assert_eq!(
+            parsed.channel.clone().map(|range| parsed.field(&range)),
+            Some("1")
+        );
+        assert_eq!(parsed.field(&parsed.id), "6e");
+        assert_eq!(parsed.dlc, 27..28);
+        assert_eq!(parsed.field(&parsed.payload), "ec 0a 22 ff ff f1");
+        assert!(parsed.rest().starts_with(" 0 0 3000"));
+    }
+}
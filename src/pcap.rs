@@ -0,0 +1,241 @@
+use std::borrow::Borrow;
+use std::io;
+use std::io::Write;
+
+use crate::CanFrame;
+
+/// The link-layer type of SocketCAN frames, see <https://www.tcpdump.org/linktypes.html>
+const LINKTYPE_CAN_SOCKETCAN: u16 = 227;
+
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 0x0000_0001;
+const ENHANCED_PACKET_BLOCK: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+
+/// Option code of the interface name, set to `can<bus>`
+const IF_NAME: u16 = 2;
+
+const CAN_EFF_FLAG: u32 = 0x8000_0000;
+const CAN_RTR_FLAG: u32 = 0x4000_0000;
+const CANFD_BRS: u8 = 0x01;
+const CANFD_ESI: u8 = 0x02;
+const CANFD_FDF: u8 = 0x04;
+
+/// Writes frames as a pcapng file with the SocketCAN link-layer type, e.g. for Wireshark
+///
+/// Every bus gets its own interface named `can<bus>`, described right before its first frame.
+/// Timestamps are written with microsecond resolution and taken as seconds since the Unix
+/// epoch, so frames of logs with relative timestamps end up in January 1970 unless shifted.
+pub struct PcapngWriter<W: Write> {
+    writer: W,
+    header_written: bool,
+    interfaces: Vec<u16>,
+}
+
+impl<W> PcapngWriter<W>
+where
+    W: Write,
+{
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header_written: false,
+            interfaces: vec![],
+        }
+    }
+
+    pub fn write_frame(&mut self, frame: &CanFrame) -> io::Result<()> {
+        self.write_header()?;
+        let interface = self.interface(frame.bus_id)?;
+
+        let packet = socketcan_packet(frame);
+        let micros = (frame.timestamp * 1e6).round().max(0.0) as u64;
+        let mut body = Vec::with_capacity(20 + packet.len());
+        body.extend_from_slice(&interface.to_le_bytes());
+        body.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        body.extend_from_slice(&(micros as u32).to_le_bytes());
+        body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+        body.extend_from_slice(&packet);
+        pad(&mut body);
+        self.write_block(ENHANCED_PACKET_BLOCK, &body)
+    }
+
+    /// Writes all frames of the iterator and flushes the underlying writer
+    pub fn write_frames<I>(&mut self, frames: I) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CanFrame>,
+    {
+        for frame in frames {
+            self.write_frame(frame.borrow())?;
+        }
+        self.flush()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_header()?;
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if !self.header_written {
+            let mut body = vec![];
+            body.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+            body.extend_from_slice(&1u16.to_le_bytes());
+            body.extend_from_slice(&0u16.to_le_bytes());
+            // The section length is not known up front
+            body.extend_from_slice(&(-1i64).to_le_bytes());
+            self.write_block(SECTION_HEADER_BLOCK, &body)?;
+            self.header_written = true;
+        }
+        Ok(())
+    }
+
+    /// The index of the interface of the given bus, describing it on first use
+    fn interface(&mut self, bus_id: u16) -> io::Result<u32> {
+        if let Some(index) = self.interfaces.iter().position(|bus| *bus == bus_id) {
+            return Ok(index as u32);
+        }
+
+        let name = format!("can{}", bus_id);
+        let mut body = vec![];
+        body.extend_from_slice(&LINKTYPE_CAN_SOCKETCAN.to_le_bytes());
+        body.extend_from_slice(&0u16.to_le_bytes());
+        // No limit on the captured length
+        body.extend_from_slice(&0u32.to_le_bytes());
+        body.extend_from_slice(&IF_NAME.to_le_bytes());
+        body.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        body.extend_from_slice(name.as_bytes());
+        pad(&mut body);
+        // End of options
+        body.extend_from_slice(&[0; 4]);
+        self.write_block(INTERFACE_DESCRIPTION_BLOCK, &body)?;
+
+        self.interfaces.push(bus_id);
+        Ok(self.interfaces.len() as u32 - 1)
+    }
+
+    /// Writes a block, the body needs to be padded to 32 bits already
+    fn write_block(&mut self, block_type: u32, body: &[u8]) -> io::Result<()> {
+        let length = (body.len() as u32 + 12).to_le_bytes();
+        self.writer.write_all(&block_type.to_le_bytes())?;
+        self.writer.write_all(&length)?;
+        self.writer.write_all(body)?;
+        self.writer.write_all(&length)
+    }
+}
+
+/// Encodes a frame like the `can_frame` and `canfd_frame` structs of SocketCAN
+///
+/// The id and flags are written in network byte order as the link-layer type requires.
+fn socketcan_packet(frame: &CanFrame) -> Vec<u8> {
+    let mut id = frame.id;
+    if frame.is_extended {
+        id |= CAN_EFF_FLAG;
+    }
+    if frame.is_rtr {
+        id |= CAN_RTR_FLAG;
+    }
+
+    let (data_length, flags) = match frame.is_fd {
+        true => {
+            let mut flags = CANFD_FDF;
+            if frame.brs {
+                flags |= CANFD_BRS;
+            }
+            if frame.esi {
+                flags |= CANFD_ESI;
+            }
+            (64, flags)
+        }
+        false => (8, 0),
+    };
+
+    let mut packet = Vec::with_capacity(8 + data_length);
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.push(frame.payload.len() as u8);
+    packet.extend_from_slice(&[flags, 0, 0]);
+    packet.extend_from_slice(&frame.payload);
+    packet.resize(8 + data_length, 0);
+    packet
+}
+
+fn pad(body: &mut Vec<u8>) {
+    body.resize(body.len().div_ceil(4) * 4, 0);
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::convert::TryInto;
+    use std::str::FromStr;
+
+    fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    /// Splits the output into blocks, checking that leading and trailing lengths agree
+    fn blocks(output: &[u8]) -> Vec<(u32, &[u8])> {
+        let mut blocks = vec![];
+        let mut rest = output;
+        while !rest.is_empty() {
+            let length = u32_at(rest, 4) as usize;
+            assert_eq!(length % 4, 0);
+            assert_eq!(u32_at(rest, length - 4) as usize, length);
+            blocks.push((u32_at(rest, 0), &rest[8..length - 4]));
+            rest = &rest[length..];
+        }
+        blocks
+    }
+
+    #[test]
+    fn write_pcapng_blocks() {
+        let frames = [
+            CanFrame::from_str("0.962604 3 368 Rx d 4 cc 55 01 00").unwrap(),
+            CanFrame::from_str("0.962892 3 1f78c410x Rx d 8 02 00 00 00 24 00 70 03").unwrap(),
+            CanFrame::from_str(
+                "7.392600 CANFD 1 Rx 6e 1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0",
+            )
+            .unwrap(),
+        ];
+        let mut writer = PcapngWriter::new(Vec::new());
+        writer
+            .write_frames(&frames)
+            .expect("Failed to write frames");
+        let output = writer.into_inner();
+
+        let blocks = blocks(&output);
+        let types: Vec<u32> = blocks.iter().map(|(block_type, _)| *block_type).collect();
+        assert_eq!(types, vec![SECTION_HEADER_BLOCK, 1, 6, 6, 1, 6]);
+
+        let (_, section) = blocks[0];
+        assert_eq!(u32_at(section, 0), BYTE_ORDER_MAGIC);
+
+        let (_, interface) = blocks[1];
+        assert_eq!(&interface[..2], &LINKTYPE_CAN_SOCKETCAN.to_le_bytes());
+        assert_eq!(&interface[12..16], b"can3");
+
+        let (_, packet) = blocks[2];
+        assert_eq!(u32_at(packet, 0), 0);
+        assert_eq!(u32_at(packet, 8), 962604);
+        assert_eq!(u32_at(packet, 12), 16);
+        assert_eq!(
+            &packet[20..36],
+            &[0, 0, 0x03, 0x68, 4, 0, 0, 0, 0xCC, 0x55, 0x01, 0x00, 0, 0, 0, 0]
+        );
+
+        let (_, extended) = blocks[3];
+        assert_eq!(&extended[20..24], &[0x9F, 0x78, 0xC4, 0x10]);
+
+        let (_, fd) = blocks[5];
+        assert_eq!(u32_at(fd, 0), 1);
+        assert_eq!(u32_at(fd, 12), 72);
+        assert_eq!(&fd[24..26], &[6, CANFD_FDF | CANFD_BRS]);
+    }
+}
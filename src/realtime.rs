@@ -0,0 +1,85 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::CanFrame;
+
+/// An iterator yielding frames at the pace they were logged at, e.g. to replay them onto a bus
+///
+/// Before each frame it sleeps for the time that passed since the frame before it, divided by
+/// the speed. Time spent by the consumer counts towards the delay, so the replay does not drift.
+/// Frames logged before their predecessor are yielded right away.
+pub struct Realtime<I> {
+    frames: I,
+    speed: f64,
+    last: Option<(f64, Instant)>,
+}
+
+impl<I> Realtime<I>
+where
+    I: Iterator<Item = CanFrame>,
+{
+    /// Paces the frames of any iterator, see [`crate::AscParser::realtime`]
+    ///
+    /// # Panics
+    ///
+    /// If the speed is not positive.
+    pub fn new(frames: I, speed: f64) -> Self {
+        assert!(speed > 0.0, "replay speed must be positive, got {}", speed);
+        Self {
+            frames,
+            speed,
+            last: None,
+        }
+    }
+}
+
+impl<I> Iterator for Realtime<I>
+where
+    I: Iterator<Item = CanFrame>,
+{
+    type Item = CanFrame;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.frames.next()?;
+        let deadline = match self.last {
+            Some((timestamp, deadline)) => {
+                let delta = (frame.timestamp - timestamp).max(0.0) / self.speed;
+                deadline + Duration::from_secs_f64(delta)
+            }
+            None => Instant::now(),
+        };
+        let now = Instant::now();
+        if deadline > now {
+            thread::sleep(deadline - now);
+        }
+        self.last = Some((frame.timestamp, deadline));
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::AscParser;
+
+    #[test]
+    fn replay_at_logged_pace() {
+        let input = "0.00 1 100 Rx d 1 01\n0.04 1 100 Rx d 1 02\n0.02 1 100 Rx d 1 03\n\
+                     0.06 1 100 Rx d 1 04\n";
+
+        let start = Instant::now();
+        let elapsed: Vec<(u8, Duration)> = AscParser::new(input.as_bytes())
+            .realtime(2.0)
+            .map(|frame| (frame.payload[0], start.elapsed()))
+            .collect();
+
+        let payloads: Vec<u8> = elapsed.iter().map(|(payload, _)| *payload).collect();
+        assert_eq!(payloads, vec![0x01, 0x02, 0x03, 0x04]);
+        assert!(elapsed.windows(2).all(|pair| pair[0].1 <= pair[1].1));
+        assert!(elapsed[1].1 >= Duration::from_millis(20));
+        // The frame logged out of order is not delayed, the next one waits for the rest
+        assert!(elapsed[3].1 >= Duration::from_millis(30));
+        assert!(elapsed[3].1 < Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{CanFrame, Realtime};
+
+/// Transmits frames one by one, e.g. onto a CAN interface
+pub trait FrameSender {
+    type Error;
+
+    fn send(&mut self, frame: &CanFrame) -> Result<(), Self::Error>;
+}
+
+/// Sends frames at the pace they were logged at, returning the number of frames sent
+///
+/// A speed of 2.0 replays twice as fast, see [`Realtime`]. Sending stops at the first frame
+/// the sender fails to send.
+///
+/// # Panics
+///
+/// If the speed is not positive.
+pub fn replay<I, S>(frames: I, sender: &mut S, speed: f64) -> Result<usize, S::Error>
+where
+    I: IntoIterator<Item = CanFrame>,
+    S: FrameSender + ?Sized,
+{
+    let mut sent = 0;
+    for frame in Realtime::new(frames.into_iter(), speed) {
+        sender.send(&frame)?;
+        sent += 1;
+    }
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::AscParser;
+    use std::time::{Duration, Instant};
+
+    /// Records the frames sent along with the time they were sent at
+    struct MockSender {
+        start: Instant,
+        sent: Vec<(u32, Duration)>,
+        capacity: usize,
+    }
+
+    impl FrameSender for MockSender {
+        type Error = &'static str;
+
+        fn send(&mut self, frame: &CanFrame) -> Result<(), Self::Error> {
+            if self.sent.len() == self.capacity {
+                return Err("bus off");
+            }
+            self.sent.push((frame.id, self.start.elapsed()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn replay_onto_mock_sender() {
+        let input = "0.00 1 100 Rx d 1 01\n0.02 1 101 Rx d 1 02\n0.04 1 102 Rx d 1 03\n";
+        let mut sender = MockSender {
+            start: Instant::now(),
+            sent: vec![],
+            capacity: 3,
+        };
+
+        let sent = replay(AscParser::new(input.as_bytes()), &mut sender, 2.0);
+        assert_eq!(sent, Ok(3));
+        let ids: Vec<u32> = sender.sent.iter().map(|(id, _)| *id).collect();
+        assert_eq!(ids, vec![0x100, 0x101, 0x102]);
+        assert!(sender.sent[2].1 >= Duration::from_millis(20));
+
+        sender.sent.clear();
+        sender.capacity = 1;
+        let sent = replay(AscParser::new(input.as_bytes()), &mut sender, 100.0);
+        assert_eq!(sent, Err("bus off"));
+        assert_eq!(sender.sent.len(), 1);
+    }
+}
@@ -0,0 +1,200 @@
+use std::convert::TryFrom;
+use std::io;
+
+use socketcan::id::FdFlags;
+use socketcan::{
+    CanAnyFrame, CanDataFrame, CanFdFrame, CanFdSocket, CanRemoteFrame, EmbeddedFrame, ExtendedId,
+    Id, Socket, StandardId,
+};
+use thiserror::Error;
+
+use crate::{CanFrame, FrameSender};
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FrameConversionError {
+    #[error("Frame id {id:#x} exceeds the identifier range")]
+    InvalidId { id: u32 },
+
+    #[error("Payload of {len} bytes exceeds the maximum of {max} bytes")]
+    PayloadTooLong { len: usize, max: usize },
+
+    #[error("Remote frame cannot be sent as CAN FD frame")]
+    RemoteFdFrame,
+}
+
+fn socketcan_id(frame: &CanFrame) -> Result<Id, FrameConversionError> {
+    match frame.is_extended {
+        true => ExtendedId::new(frame.id).map(Id::Extended),
+        false => u16::try_from(frame.id)
+            .ok()
+            .and_then(StandardId::new)
+            .map(Id::Standard),
+    }
+    .ok_or(FrameConversionError::InvalidId { id: frame.id })
+}
+
+impl TryFrom<&CanFrame> for CanDataFrame {
+    type Error = FrameConversionError;
+
+    fn try_from(frame: &CanFrame) -> Result<Self, Self::Error> {
+        let id = socketcan_id(frame)?;
+        CanDataFrame::new(id, &frame.payload).ok_or(FrameConversionError::PayloadTooLong {
+            len: frame.payload.len(),
+            max: 8,
+        })
+    }
+}
+
+impl TryFrom<&CanFrame> for CanRemoteFrame {
+    type Error = FrameConversionError;
+
+    fn try_from(frame: &CanFrame) -> Result<Self, Self::Error> {
+        let id = socketcan_id(frame)?;
+        CanRemoteFrame::new_remote(id, frame.length).ok_or(FrameConversionError::PayloadTooLong {
+            len: frame.length,
+            max: 8,
+        })
+    }
+}
+
+impl TryFrom<&CanFrame> for CanFdFrame {
+    type Error = FrameConversionError;
+
+    fn try_from(frame: &CanFrame) -> Result<Self, Self::Error> {
+        let id = socketcan_id(frame)?;
+        let mut flags = FdFlags::empty();
+        if frame.brs {
+            flags |= FdFlags::BRS;
+        }
+        if frame.esi {
+            flags |= FdFlags::ESI;
+        }
+        CanFdFrame::with_flags(id, &frame.payload, flags).ok_or(
+            FrameConversionError::PayloadTooLong {
+                len: frame.payload.len(),
+                max: 64,
+            },
+        )
+    }
+}
+
+/// Picks a classic data, remote or CAN FD frame depending on the frame content
+impl TryFrom<&CanFrame> for CanAnyFrame {
+    type Error = FrameConversionError;
+
+    fn try_from(frame: &CanFrame) -> Result<Self, FrameConversionError> {
+        match (frame.is_fd, frame.is_rtr) {
+            (true, true) => Err(FrameConversionError::RemoteFdFrame),
+            (true, false) => CanFdFrame::try_from(frame).map(CanAnyFrame::Fd),
+            (false, true) => CanRemoteFrame::try_from(frame).map(CanAnyFrame::Remote),
+            (false, false) => CanDataFrame::try_from(frame).map(CanAnyFrame::Normal),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SendError {
+    #[error(transparent)]
+    Conversion(#[from] FrameConversionError),
+
+    #[error("Cannot write frame: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Sends classic as well as CAN FD frames, the interface needs to support CAN FD for the latter
+impl FrameSender for CanFdSocket {
+    type Error = SendError;
+
+    fn send(&mut self, frame: &CanFrame) -> Result<(), Self::Error> {
+        let frame = CanAnyFrame::try_from(frame)?;
+        self.write_frame(&frame)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn convert_classic_frame() {
+        let line =
+            String::from("0.962892 3 1f78c410x Rx d 8 02 00 00 00 24 00 70 03 Length = 0 BitCount = 0 ID = 528008208x");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+
+        let socket_frame = CanDataFrame::try_from(&frame).expect("Failed to convert frame");
+        assert_eq!(
+            socket_frame.id(),
+            Id::Extended(ExtendedId::new(0x1f78c410).unwrap())
+        );
+        assert_eq!(
+            socket_frame.data(),
+            &[0x02, 0x00, 0x00, 0x00, 0x24, 0x00, 0x70, 0x03]
+        );
+
+        assert!(matches!(
+            CanAnyFrame::try_from(&frame),
+            Ok(CanAnyFrame::Normal(_))
+        ));
+    }
+
+    #[test]
+    fn convert_fd_frame() {
+        let line_canfd =
+            String::from("7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0");
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+
+        match CanAnyFrame::try_from(&frame) {
+            Ok(CanAnyFrame::Fd(socket_frame)) => {
+                assert_eq!(
+                    socket_frame.id(),
+                    Id::Standard(StandardId::new(0x6e).unwrap())
+                );
+                assert!(socket_frame.is_brs());
+                assert!(!socket_frame.is_esi());
+                assert_eq!(socket_frame.data(), &[0xEC, 0x0A, 0x22, 0xFF, 0xFF, 0xF1]);
+            }
+            other => panic!("Unexpected conversion result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn convert_remote_frame() {
+        let line = String::from("1.234567 1 123 Rx r 8 Length = 0 BitCount = 0 ID = 291");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+
+        assert!(matches!(
+            CanAnyFrame::try_from(&frame),
+            Ok(CanAnyFrame::Remote(_))
+        ));
+    }
+
+    #[test]
+    fn reject_oversized_classic_frame() {
+        let payload = ["aa"; 12].join(" ");
+        let line_canfd = format!(
+            "7.392600 CANFD 1 Rx 6e 1 0 9 12 {} 0 0 3000 0 0 0 0 0",
+            payload
+        );
+        let frame = CanFrame::from_str(&line_canfd).expect("Uncaught error while parsing");
+
+        assert_eq!(
+            CanDataFrame::try_from(&frame).err(),
+            Some(FrameConversionError::PayloadTooLong { len: 12, max: 8 })
+        );
+    }
+
+    #[test]
+    fn reject_out_of_range_id() {
+        let line =
+            String::from("0.962604 3 7ff0 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872");
+        let frame = CanFrame::from_str(&line).expect("Uncaught error while parsing");
+
+        assert_eq!(
+            CanDataFrame::try_from(&frame).err(),
+            Some(FrameConversionError::InvalidId { id: 0x7ff0 })
+        );
+    }
+}
@@ -0,0 +1,107 @@
+use std::str::FromStr;
+
+/// The summary of a bus written by Vector loggers in `Statistic:` lines
+///
+/// A line like `1.000000 1 Statistic: D 1234 R 5 XD 0 XR 0 E 2 O 0 B 12.34%` reports the frames
+/// a channel saw since the previous summary. Loggers write them periodically or once at the end
+/// of the measurement, timestamp and channel are left out by some.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BusStatistics {
+    pub timestamp: Option<f64>,
+    pub bus_id: Option<u16>,
+    /// Standard data frames, `D`
+    pub data_frames: u64,
+    /// Standard remote frames, `R`
+    pub remote_frames: u64,
+    /// Extended data frames, `XD`
+    pub extended_data_frames: u64,
+    /// Extended remote frames, `XR`
+    pub extended_remote_frames: u64,
+    /// Error frames, `E`
+    pub error_frames: u64,
+    /// Overload frames, `O`
+    pub overload_frames: u64,
+    /// The bus load in percent, `B`
+    pub bus_load: f64,
+}
+
+impl BusStatistics {
+    /// Parses a `Statistic:` line, returning `None` for any other line
+    ///
+    /// Counters that are missing or cannot be parsed are left at zero.
+    pub fn parse_line(line: &str) -> Option<Self> {
+        // The keyword follows the optional timestamp and channel
+        let mut tokens = line.split_whitespace();
+        if !tokens.clone().take(3).any(|token| token == "Statistic:") {
+            return None;
+        }
+        let leading: Vec<&str> = tokens.by_ref().take_while(|t| *t != "Statistic:").collect();
+
+        let mut statistics = BusStatistics::default();
+        match leading.as_slice() {
+            [] => {}
+            [timestamp] => statistics.timestamp = Some(f64::from_str(timestamp).ok()?),
+            [timestamp, bus_id] => {
+                statistics.timestamp = Some(f64::from_str(timestamp).ok()?);
+                statistics.bus_id = Some(u16::from_str(bus_id).ok()?);
+            }
+            _ => return None,
+        }
+
+        while let (Some(key), Some(value)) = (tokens.next(), tokens.next()) {
+            let count = || u64::from_str(value).unwrap_or(0);
+            match key {
+                "D" => statistics.data_frames = count(),
+                "R" => statistics.remote_frames = count(),
+                "XD" => statistics.extended_data_frames = count(),
+                "XR" => statistics.extended_remote_frames = count(),
+                "E" => statistics.error_frames = count(),
+                "O" => statistics.overload_frames = count(),
+                "B" => {
+                    statistics.bus_load = f64::from_str(value.trim_end_matches('%')).unwrap_or(0.0)
+                }
+                _ => {}
+            }
+        }
+        Some(statistics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parse_statistic_line() {
+        let statistics =
+            BusStatistics::parse_line("Statistic: D 1234 R 5 XD 0 XR 1 E 2 O 0 B 12.34%").unwrap();
+        assert_eq!(
+            statistics,
+            BusStatistics {
+                timestamp: None,
+                bus_id: None,
+                data_frames: 1234,
+                remote_frames: 5,
+                extended_data_frames: 0,
+                extended_remote_frames: 1,
+                error_frames: 2,
+                overload_frames: 0,
+                bus_load: 12.34,
+            }
+        );
+
+        let statistics = BusStatistics::parse_line(
+            "   7.500000 2  Statistic: D 10 R 0 XD 3 XR 0 E 0 O 0 B 0.05%",
+        )
+        .unwrap();
+        assert_eq!(statistics.timestamp, Some(7.5));
+        assert_eq!(statistics.bus_id, Some(2));
+        assert_eq!(statistics.extended_data_frames, 3);
+
+        assert_eq!(BusStatistics::parse_line("0.5 1 123 Rx d 1 01"), None);
+        assert_eq!(BusStatistics::parse_line("0.5 Statistics"), None);
+        assert_eq!(BusStatistics::parse_line(""), None);
+    }
+}
@@ -0,0 +1,107 @@
+use std::borrow::Borrow;
+use std::io;
+use std::io::Write;
+
+use crate::CanFrame;
+
+/// Writes frames as a Vector ASC file
+///
+/// A minimal header declaring hex ids and absolute timestamps is written ahead of the first
+/// frame, or on `flush` if no frame was written at all.
+pub struct AscWriter<W: Write> {
+    writer: W,
+    header_written: bool,
+}
+
+impl<W> AscWriter<W>
+where
+    W: Write,
+{
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            header_written: false,
+        }
+    }
+
+    pub fn write_frame(&mut self, frame: &CanFrame) -> io::Result<()> {
+        self.write_header()?;
+        writeln!(self.writer, "{}", frame.to_asc_line())
+    }
+
+    /// Writes all frames of the iterator and flushes the underlying writer
+    pub fn write_frames<I>(&mut self, frames: I) -> io::Result<()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<CanFrame>,
+    {
+        for frame in frames {
+            self.write_frame(frame.borrow())?;
+        }
+        self.flush()
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.write_header()?;
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_header(&mut self) -> io::Result<()> {
+        if !self.header_written {
+            writeln!(self.writer, "base hex  timestamps absolute")?;
+            writeln!(self.writer, "no internal events logged")?;
+            self.header_written = true;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::AscParser;
+    use std::str::FromStr;
+
+    #[test]
+    fn write_and_read_back() {
+        let lines = [
+            "0.962604 3 368 Rx d 4 cc 55 01 00 Length = 0 BitCount = 0 ID = 872",
+            "0.962892 3 1f78c410x Tx d 8 02 00 00 00 24 00 70 03 Length = 0 BitCount = 0 ID = 528008208x",
+            "1.234567 1 12 Rx r 8 Length = 0 BitCount = 0 ID = 18",
+            "7.392600 CANFD 1 Rx 6e   1 0 6 6 ec 0a 22 ff ff f1 0 0 3000 0 0 0 0 0",
+            "7.392600 CANFD 1 Tx 12b80210x 0 1 9 12 00 01 02 03 04 05 06 07 08 09 0a 0b 0 0 5000 0 0 0 0 0",
+        ];
+        let frames: Vec<CanFrame> = lines
+            .iter()
+            .map(|line| CanFrame::from_str(line).expect("Uncaught error while parsing"))
+            .collect();
+
+        let mut writer = AscWriter::new(Vec::new());
+        writer
+            .write_frames(&frames)
+            .expect("Failed to write frames");
+        let output = writer.into_inner();
+
+        let parsed: Vec<CanFrame> = AscParser::new(output.as_slice())
+            .try_frames()
+            .collect::<Result<_, _>>()
+            .expect("Failed to parse written frames");
+        assert_eq!(parsed, frames);
+    }
+
+    #[test]
+    fn write_header_without_frames() {
+        let mut writer = AscWriter::new(Vec::new());
+        writer.flush().expect("Failed to flush");
+        let output = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(
+            output,
+            "base hex  timestamps absolute\nno internal events logged\n"
+        );
+    }
+}
@@ -0,0 +1,719 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn fixture(name: &str) -> String {
+    format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn ascdump(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ascdump"))
+        .args(args)
+        .output()
+        .expect("Failed to run ascdump")
+}
+
+fn ascdump_with_stdin(args: &[&str], input: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ascdump"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to run ascdump");
+    child
+        .stdin
+        .take()
+        .expect("Failed to open stdin")
+        .write_all(input)
+        .expect("Failed to write to stdin");
+    child
+        .wait_with_output()
+        .expect("Failed to wait for ascdump")
+}
+
+fn stdout_lines(output: &Output) -> Vec<String> {
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn print_help() {
+    let output = ascdump(&["--help"]);
+    assert!(output.status.success());
+    let help = String::from_utf8_lossy(&output.stdout);
+    assert!(help.contains("Usage: ascdump"));
+    assert!(help.contains("[INPUT]..."));
+    assert!(help.contains("--format <format>"));
+}
+
+#[test]
+fn print_all_frames() {
+    let output = ascdump(&[&fixture("sample.asc")]);
+    assert!(output.status.success());
+    assert_eq!(stdout_lines(&output).len(), 6);
+}
+
+#[test]
+fn filter_by_id() {
+    let output = ascdump(&[&fixture("sample.asc"), "--id", "0x368", "--id", "1f78c410x"]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains("id: 872,"));
+    assert!(lines[1].contains("id: 528008208,"));
+    assert!(lines[2].contains("id: 872,"));
+
+    let output = ascdump(&[&fixture("sample.asc"), "--id", "6e"]);
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].contains("id: 110,"));
+}
+
+#[test]
+fn filter_by_id_file() {
+    let path = std::env::temp_dir().join(format!("ascdump-ids-{}.txt", std::process::id()));
+    std::fs::write(
+        &path,
+        "# ids of interest\n368\n0x123 // gateway\n\n1f78c410x\n",
+    )
+    .expect("Failed to write id file");
+    let path = path.to_string_lossy().into_owned();
+
+    let output = ascdump(&[&fixture("sample.asc"), "--id-file", &path]);
+    assert!(output.status.success());
+    assert_eq!(stdout_lines(&output).len(), 5);
+
+    let output = ascdump(&[&fixture("sample.asc"), "--exclude-id-file", &path]);
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 1);
+    assert!(lines[0].contains("id: 110,"));
+
+    std::fs::write(&path, "368\nzz\n").expect("Failed to write id file");
+    let output = ascdump(&[&fixture("sample.asc"), "--id-file", &path]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains(":2: invalid CAN id"));
+
+    std::fs::remove_file(&path).expect("Failed to remove id file");
+}
+
+#[test]
+fn reject_invalid_id() {
+    let output = ascdump(&[&fixture("sample.asc"), "--id", "zz"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn filter_by_bus() {
+    let output = ascdump(&[&fixture("sample.asc"), "--bus", "1"]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 3);
+    assert!(lines.iter().all(|line| line.contains("bus_id: 1,")));
+
+    let output = ascdump(&[&fixture("sample.asc"), "--bus", "1", "--bus", "3"]);
+    assert_eq!(stdout_lines(&output).len(), 6);
+}
+
+#[test]
+fn remap_bus() {
+    let output = ascdump(&[&fixture("sample.asc"), "--remap", "1:5", "--bus", "5"]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 3);
+    assert!(lines.iter().all(|line| line.contains("bus_id: 5,")));
+
+    let output = ascdump(&[&fixture("sample.asc"), "--remap", "1"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn filter_by_bus_and_id() {
+    let output = ascdump(&[&fixture("sample.asc"), "--bus", "1", "--id", "123"]);
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 2);
+    assert!(lines
+        .iter()
+        .all(|line| line.contains("bus_id: 1,") && line.contains("id: 291,")));
+
+    let output = ascdump(&[&fixture("sample.asc"), "--bus", "1", "--id", "368"]);
+    assert!(stdout_lines(&output).is_empty());
+}
+
+#[test]
+fn filter_by_payload_byte() {
+    let output = ascdump(&["--format", "csv", "--match", "0:cc", &fixture("sample.asc")]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout_lines(&output),
+        vec![
+            "timestamp,bus,id,length,payload",
+            "0.962604,3,00000368,4,CC 55 01 00",
+            "1.500000,3,00000368,4,CC 55 02 00",
+        ]
+    );
+
+    let output = ascdump(&[
+        "--format",
+        "csv",
+        "--match",
+        "0:cc",
+        "--match",
+        "2:0x02/0x0e",
+        &fixture("sample.asc"),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout_lines(&output)[1..],
+        ["1.500000,3,00000368,4,CC 55 02 00"]
+    );
+
+    let output = ascdump(&["--match", "0:zz", &fixture("sample.asc")]);
+    assert!(!output.status.success());
+}
+
+#[cfg(feature = "dbc")]
+#[test]
+fn print_decoded_signals() {
+    let output = ascdump(&["--dbc", &fixture("sample.dbc"), &fixture("sample.asc")]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout_lines(&output),
+        vec![
+            "0.962604 can3 368 Mode=204 Speed=160.5 Temperature=1",
+            "0.962892 can3 1F78C410 Counter=9216",
+            "1.500000 can3 368 Mode=204 Speed=288.5 Temperature=2",
+        ]
+    );
+
+    let output = ascdump(&["--dbc", &fixture("missing.dbc"), &fixture("sample.asc")]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[cfg(feature = "dbc")]
+#[test]
+fn print_signal_table() {
+    let output = ascdump(&[
+        "signals",
+        &fixture("sample.asc"),
+        "--dbc",
+        &fixture("sample.dbc"),
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout_lines(&output),
+        vec![
+            "timestamp,Status.Mode,Status.Speed,Status.Temperature,Extended.Counter",
+            "0.962604,204,160.5,1,",
+            "0.962892,204,160.5,1,9216",
+            "1.500000,204,288.5,2,9216",
+        ]
+    );
+
+    let input = b"0.1 1 100 Rx d 2 00 05\n0.2 1 200 Rx d 1 07\n0.3 1 100 Rx d 2 01 09\n";
+    let output = ascdump_with_stdin(
+        &["signals", "-", "--dbc", &fixture("multiplexed.dbc")],
+        input,
+    );
+    assert_eq!(
+        stdout_lines(&output),
+        vec![
+            "timestamp,Mux.Selector,Mux.A,Mux.B,Plain.Value",
+            "0.100000,0,5,,",
+            "0.200000,0,,,7",
+            "0.300000,1,,9,7",
+        ]
+    );
+}
+
+#[test]
+fn select_time_range() {
+    let output = ascdump(&[&fixture("sample.asc"), "--from", "1.0", "--to", "2.0"]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains("timestamp: 1.0,"));
+    assert!(lines[2].contains("timestamp: 2.0,"));
+
+    let output = ascdump(&[&fixture("sample.asc"), "--from", "1.5"]);
+    assert_eq!(stdout_lines(&output).len(), 3);
+
+    let output = ascdump(&[&fixture("sample.asc"), "--to", "0.962892"]);
+    assert_eq!(stdout_lines(&output).len(), 2);
+}
+
+#[test]
+fn reject_inverted_time_range() {
+    let output = ascdump(&[&fixture("sample.asc"), "--from", "2.0", "--to", "1.0"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--from"));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn print_absolute_time() {
+    let output = ascdump(&[&fixture("sample.asc"), "--absolute-time"]);
+    assert!(output.status.success());
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 6);
+    assert!(lines[0].starts_with("CanFrame { timestamp: 2020-09-14T10:11:13.307604, bus_id: 3,"));
+    assert!(lines[0].contains("payload: [204, 85, 1, 0],"));
+
+    let output = ascdump(&[&fixture("sample.asc"), "--absolute-time", "--format", "csv"]);
+    assert_eq!(
+        stdout_lines(&output)[1],
+        "2020-09-14T10:11:13.307604,3,00000368,4,CC 55 01 00"
+    );
+
+    let output = ascdump(&[
+        &fixture("sample.asc"),
+        "--absolute-time",
+        "--format",
+        "candumplog",
+    ]);
+    assert_eq!(
+        stdout_lines(&output)[0],
+        "(1600078273.307604) can3 368#CC550100"
+    );
+
+    // Logs without a date keep their relative timestamps
+    let output = ascdump_with_stdin(
+        &["--absolute-time"],
+        b"0.5 1 123 Rx d 1 01\n0.6 1 123 Rx d 1 02\n",
+    );
+    assert!(output.status.success());
+    assert!(stdout_lines(&output)[0].starts_with("CanFrame { timestamp: 0.5,"));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(
+        stderr.matches("warning: <stdin> declares no date").count(),
+        1
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn print_json_lines() {
+    let output = ascdump(&[&fixture("sample.asc"), "--format", "json"]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 6);
+
+    let first: serde_json::Value = serde_json::from_str(&lines[0]).expect("Invalid JSON");
+    assert_eq!(
+        first,
+        serde_json::json!({
+            "timestamp": 0.962604,
+            "bus": 3,
+            "id": 0x368,
+            "length": 4,
+            "payload": [0xcc, 0x55, 0x01, 0x00],
+        })
+    );
+}
+
+#[test]
+fn print_csv() {
+    let output = ascdump(&[&fixture("sample.asc"), "--format", "csv"]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 7);
+    assert_eq!(lines[0], "timestamp,bus,id,length,payload");
+    assert_eq!(lines[1], "0.962604,3,00000368,4,CC 55 01 00");
+    assert_eq!(lines[2], "0.962892,3,1F78C410,8,02 00 00 00 24 00 70 03");
+}
+
+#[test]
+fn print_payload_in_requested_base() {
+    let output = ascdump(&[&fixture("sample.asc"), "--payload-format", "hex"]);
+    assert!(output.status.success());
+    let lines = stdout_lines(&output);
+    assert!(lines[0].contains("payload: [CC, 55, 01, 00],"));
+    assert!(lines[5].ends_with("fd_flags: Some(FdFlags(12288)) }"));
+
+    let output = ascdump(&[&fixture("sample.asc"), "--payload-format", "dec"]);
+    assert!(stdout_lines(&output)[0].contains("payload: [204, 85, 1, 0],"));
+    // Apart from the payload, all fields match the plain debug output
+    let plain = stdout_lines(&ascdump(&[&fixture("sample.asc")]));
+    assert_eq!(stdout_lines(&output), plain);
+    for (hex, plain) in lines.iter().zip(&plain) {
+        let fields = |line: &str| -> Vec<String> {
+            line.split(", ")
+                .filter(|field| field.contains(": ") && !field.contains("payload: "))
+                .map(String::from)
+                .collect()
+        };
+        assert_eq!(fields(hex), fields(plain));
+    }
+
+    let output = ascdump(&[
+        &fixture("sample.asc"),
+        "--format",
+        "csv",
+        "--payload-format",
+        "bin",
+    ]);
+    assert_eq!(
+        stdout_lines(&output)[1],
+        "0.962604,3,00000368,4,11001100 01010101 00000001 00000000"
+    );
+}
+
+#[test]
+fn print_candump_log() {
+    let output = ascdump(&[&fixture("sample.asc"), "--format", "candumplog"]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[0], "(0.962604) can3 368#CC550100");
+    assert_eq!(lines[1], "(0.962892) can3 1F78C410#0200000024007003");
+    assert_eq!(lines[5], "(7.392600) can1 06E##1EC0A22FFFFF1");
+
+    let output = ascdump_with_stdin(&["--format", "candumplog"], b"0.5 1 12 Rx r 8\n");
+    assert_eq!(stdout_lines(&output), vec!["(0.500000) can1 012#R"]);
+}
+
+#[test]
+fn write_pcapng() {
+    let output = ascdump(&[&fixture("sample.asc"), "--format", "pcap"]);
+    assert!(output.status.success());
+
+    let pcap = output.stdout;
+    assert_eq!(&pcap[..4], &[0x0A, 0x0D, 0x0D, 0x0A]);
+    let section_length = u32::from_le_bytes([pcap[4], pcap[5], pcap[6], pcap[7]]) as usize;
+    assert_eq!(&pcap[section_length..section_length + 4], &[1, 0, 0, 0]);
+}
+
+#[test]
+fn read_from_stdin() {
+    let input = std::fs::read(fixture("sample.asc")).expect("Failed to read fixture");
+
+    let output = ascdump_with_stdin(&["-"], &input);
+    assert!(output.status.success());
+    assert_eq!(stdout_lines(&output).len(), 6);
+
+    let output = ascdump_with_stdin(&["--bus", "1"], &input);
+    assert!(output.status.success());
+    assert_eq!(stdout_lines(&output).len(), 3);
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn read_gzip_input() {
+    let output = ascdump(&[&fixture("sample.asc.gz")]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout_lines(&output),
+        stdout_lines(&ascdump(&[&fixture("sample.asc")]))
+    );
+
+    let input = std::fs::read(fixture("sample.asc.gz")).expect("Failed to read fixture");
+    let output = ascdump_with_stdin(&["--gzip"], &input);
+    assert!(output.status.success());
+    assert_eq!(stdout_lines(&output).len(), 6);
+}
+
+#[test]
+fn write_to_output_file() {
+    let path = std::env::temp_dir().join(format!("ascdump-output-{}.csv", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    let output = ascdump(&["--format", "csv", "-o", path, &fixture("sample.asc")]);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    let written = std::fs::read(path).expect("Failed to read output");
+    std::fs::remove_file(path).expect("Failed to remove output");
+    assert_eq!(
+        written,
+        ascdump(&["--format", "csv", &fixture("sample.asc")]).stdout
+    );
+}
+
+#[cfg(feature = "flate2")]
+#[test]
+fn write_gzip_output() {
+    use std::io::Read;
+
+    let path = std::env::temp_dir().join(format!("ascdump-output-{}.csv.gz", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    let output = ascdump(&["--format", "csv", "-o", path, &fixture("sample.asc")]);
+    assert!(output.status.success());
+    let mut written = vec![];
+    flate2::read::GzDecoder::new(std::fs::File::open(path).expect("Failed to open output"))
+        .read_to_end(&mut written)
+        .expect("Failed to decompress output");
+    std::fs::remove_file(path).expect("Failed to remove output");
+    assert_eq!(
+        written,
+        ascdump(&["--format", "csv", &fixture("sample.asc")]).stdout
+    );
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn write_zstd_output() {
+    let path = std::env::temp_dir().join(format!("ascdump-output-{}.zst", std::process::id()));
+    let path = path.to_str().unwrap();
+
+    let output = ascdump(&["-o", path, &fixture("sample.asc")]);
+    assert!(output.status.success());
+    let compressed = std::fs::read(path).expect("Failed to read output");
+    std::fs::remove_file(path).expect("Failed to remove output");
+    let written = zstd::stream::decode_all(&compressed[..]).expect("Failed to decompress output");
+    assert_eq!(written, ascdump(&[&fixture("sample.asc")]).stdout);
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn read_memory_mapped_input() {
+    let output = ascdump(&["--mmap", &fixture("sample.asc"), &fixture("second.asc")]);
+    assert!(output.status.success());
+    assert_eq!(stdout_lines(&output).len(), 8);
+    assert_eq!(
+        stdout_lines(&output),
+        stdout_lines(&ascdump(&[&fixture("sample.asc"), &fixture("second.asc")]))
+    );
+}
+
+#[test]
+fn fail_on_missing_input() {
+    let output = ascdump(&[&fixture("sample.asc"), &fixture("missing.asc")]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.starts_with("error: cannot open "));
+    assert!(stderr.contains("missing.asc"));
+    assert!(!stderr.contains("panicked"));
+
+    assert_eq!(ascdump(&[&fixture("sample.asc")]).status.code(), Some(0));
+}
+
+const INPUT_WITH_BAD_LINE: &str = "0.1 1 100 Rx d 1 01\n\
+                                   0.2 1 101 Rx d 2 02\n\
+                                   0.3 1 102 Rx d 1 03\n";
+
+#[test]
+fn skip_bad_lines_quietly() {
+    let output = ascdump_with_stdin(&["--quiet"], INPUT_WITH_BAD_LINE.as_bytes());
+    assert_eq!(output.status.code(), Some(2));
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("id: 256"));
+    assert!(lines[1].contains("id: 258"));
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn warn_about_bad_lines() {
+    let output = ascdump_with_stdin(&[], INPUT_WITH_BAD_LINE.as_bytes());
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout_lines(&output).len(), 2);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "warning: <stdin>:2: Inconsistent payload length: 2 != 1\n"
+    );
+}
+
+#[test]
+fn abort_at_bad_line_in_strict_mode() {
+    let output = ascdump_with_stdin(&["--strict"], INPUT_WITH_BAD_LINE.as_bytes());
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout_lines(&output).len(), 1);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "error: <stdin>:2: Inconsistent payload length: 2 != 1\n"
+    );
+
+    let output = ascdump_with_stdin(&["--strict", "--quiet"], INPUT_WITH_BAD_LINE.as_bytes());
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(stdout_lines(&output).len(), 1);
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn skip_error_frames_in_strict_and_quiet_mode() {
+    for flag in ["--strict", "--quiet"] {
+        let output = ascdump(&[flag, &fixture("error_frame.asc")]);
+        assert_eq!(output.status.code(), Some(0));
+        assert_eq!(stdout_lines(&output).len(), 2);
+        assert!(output.stderr.is_empty());
+    }
+}
+
+#[test]
+fn skip_clock_time_error_frames_in_strict_mode() {
+    let input = b"date Mon Sep 14 10:11:12.345 am 2020\n\
+                  base hex  timestamps absolute\n\
+                  10:11:13.307604 1 123 Rx d 1 01\n\
+                  10:11:13.407604 1 ErrorFrame\n\
+                  10:11:13.507604 1 123 Rx d 1 02\n";
+    let output = ascdump_with_stdin(&["--strict"], input);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(stdout_lines(&output).len(), 2);
+    assert!(output.stderr.is_empty());
+}
+
+#[test]
+fn concatenate_multiple_inputs() {
+    let output = ascdump(&[&fixture("sample.asc"), &fixture("second.asc")]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 8);
+    assert!(lines[5].contains("id: 110,"));
+    assert!(lines[6].contains("id: 512,"));
+    assert!(lines[7].contains("id: 513,"));
+
+    let output = ascdump(&[
+        &fixture("sample.asc"),
+        &fixture("second.asc"),
+        "--to",
+        "1.0",
+    ]);
+    assert_eq!(stdout_lines(&output).len(), 5);
+}
+
+#[test]
+fn diff_two_logs() {
+    let output = ascdump(&["diff", &fixture("sample.asc"), &fixture("changed.asc")]);
+    assert!(output.status.success());
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("--- ") && lines[0].ends_with("sample.asc"));
+    assert!(lines[1].starts_with("+++ ") && lines[1].ends_with("changed.asc"));
+    assert_eq!(lines[2], "123 -1 +1");
+
+    let output = ascdump(&[
+        "diff",
+        "--verbose",
+        &fixture("sample.asc"),
+        &fixture("changed.asc"),
+    ]);
+    let lines = stdout_lines(&output);
+    assert_eq!(lines.len(), 5);
+    assert!(lines[3].starts_with("-2.000000 1 123 Tx d 2 11 23"));
+    assert!(lines[4].starts_with("+2.000000 1 123 Tx d 2 11 24"));
+
+    let output = ascdump(&["diff", &fixture("sample.asc"), &fixture("sample.asc")]);
+    assert_eq!(stdout_lines(&output).len(), 2);
+}
+
+#[test]
+fn count_frames() {
+    let output = ascdump(&[&fixture("sample.asc"), &fixture("second.asc"), "--count"]);
+    assert!(output.status.success());
+    assert_eq!(stdout_lines(&output), vec!["8"]);
+
+    let output = ascdump(&[&fixture("sample.asc"), "--count", "--id", "368"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn print_stats() {
+    let output = ascdump(&[&fixture("sample.asc"), "--stats"]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(
+        lines,
+        vec![
+            "frames: 6",
+            "bus 1: 3",
+            "bus 3: 3",
+            "unique ids: 4",
+            "first timestamp: 0.962604",
+            "last timestamp: 7.392600",
+            "duration: 6.429996",
+            "frames per second: 0.933",
+        ]
+    );
+}
+
+#[test]
+fn print_bus_load_with_stats() {
+    let output = ascdump(&[
+        &fixture("sample.asc"),
+        "--stats",
+        "--bitrate",
+        "1:500000",
+        "--bitrate",
+        "3:125000",
+    ]);
+    assert!(output.status.success());
+    let lines = stdout_lines(&output);
+    assert_eq!(
+        lines[lines.len() - 2],
+        "bus 1 load: 0.01% average, 0.02% peak"
+    );
+    assert_eq!(
+        lines[lines.len() - 1],
+        "bus 3 load: 0.12% average, 0.17% peak"
+    );
+
+    let output = ascdump(&[&fixture("sample.asc"), "--stats", "--bitrate", "1:500000"]);
+    assert_eq!(stdout_lines(&output).last().unwrap(), "bus 3 load: unknown");
+
+    let output = ascdump(&[&fixture("sample.asc"), "--stats", "--bitrate", "1"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn print_id_histogram() {
+    let output = ascdump(&[&fixture("sample.asc"), "--histogram"]);
+    assert!(output.status.success());
+
+    let lines = stdout_lines(&output);
+    assert_eq!(
+        lines,
+        vec![
+            "123 2 33.33%",
+            "368 2 33.33%",
+            "6E 1 16.67%",
+            "1F78C410x 1 16.67%",
+        ]
+    );
+}
+
+#[test]
+fn follow_appended_frames() {
+    use std::io::{BufRead, BufReader};
+
+    let path = std::env::temp_dir().join(format!("ascdump-cli-follow-{}.asc", std::process::id()));
+    std::fs::write(
+        &path,
+        "0.1 1 101 Rx d 1 01 Length = 0 BitCount = 0 ID = 257\n",
+    )
+    .expect("Failed to write temp file");
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ascdump"))
+        .args([path.to_str().unwrap(), "--follow"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed to run ascdump");
+    let mut stdout = BufReader::new(child.stdout.take().unwrap()).lines();
+
+    let first = stdout.next().unwrap().unwrap();
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| {
+            file.write_all(b"0.2 1 102 Rx d 1 02 Length = 0 BitCount = 0 ID = 258\n")
+        })
+        .expect("Failed to append to temp file");
+    let second = stdout.next().unwrap().unwrap();
+
+    child.kill().ok();
+    child.wait().ok();
+    std::fs::remove_file(&path).ok();
+
+    assert!(first.contains("id: 257,"));
+    assert!(second.contains("id: 258,"));
+}